@@ -15,6 +15,28 @@ fn compile_protobufs() {
         "src/protobuf/message_send.proto",
         "src/protobuf/logout.proto",
         "src/protobuf/sign_in.proto",
+        // 群成员相关
+        "src/protobuf/member_update.proto",
+        "src/protobuf/message_member_change.proto",
+        "src/protobuf/message_member_level_change.proto",
+        "src/protobuf/message_member_ban.proto",
+        "src/protobuf/message_chatroom_member_delete.proto",
+        // 用户资料
+        "src/protobuf/event_user_info_update.proto",
+        // 消息投递
+        "src/protobuf/message_push.proto",
+        // 系统公告
+        "src/protobuf/event_notice.proto",
+        "src/protobuf/event_announce_notice.proto",
+        // 记事本提醒
+        "src/protobuf/event_remind_note_push.proto",
+        // 日志上传
+        "src/protobuf/event_device_log_upload.proto",
+        // 应用状态上报
+        "src/protobuf/event_apps_upload.proto",
+        // 消息中的链接
+        "src/protobuf/message_app_link.proto",
+        "src/protobuf/message_task.proto",
     ];
     
     println!("cargo:rerun-if-changed={}", proto_dir);
@@ -1,4 +1,7 @@
-use crate::{dock, tray, window};
+use crate::{
+    apps_upload, chat_windows, crash, deep_link, device_id, dock, endpoints, lifecycle, logging,
+    media_cache, notices, profiles, reminders, settings, tray, voice_broadcast, window,
+};
 use tauri::Manager;
 
 /// 应用程序配置和初始化模块
@@ -18,21 +21,27 @@ pub fn configure_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         // 单实例插件 - 防止多个应用实例同时运行
-        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
             // 这个回调会在尝试启动第二个应用实例时触发
             // app: 应用程序的 AppHandle
-            // _argv: 启动第二个实例时的命令行参数 (已添加下划线前缀表示有意未使用)
+            // argv: 启动第二个实例时的命令行参数，其中的深度链接交给当前实例处理
             // _cwd: 启动第二个实例时的当前工作目录 (已添加下划线前缀表示有意未使用)
 
             // 当尝试启动第二个实例时，显示主窗口并将其置于前台
-            window::show_main_window(&app);
+            window::show_main_window(app);
+            deep_link::handle_args(app, argv);
         }))
         .plugin(tauri_plugin_upload::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent, // macOS下使用LaunchAgent方式
             None,                                               // 不指定额外的启动参数
         ))
-        // .plugin(tauri_plugin_window_state::Builder::new().build())
+        // 窗口状态插件 - 保存并恢复所有窗口的大小和位置
+        .plugin(
+            tauri_plugin_window_state::Builder::new()
+                .with_state_flags(window::window_state_flags())
+                .build(),
+        )
         .plugin(tauri_plugin_opener::init()) // 用于打开外部 URL
         .plugin(tauri_plugin_dialog::init()) // 用于显示文件对话框
         .plugin(tauri_plugin_clipboard_manager::init()) // 用于粘贴板操作
@@ -51,9 +60,68 @@ pub fn configure_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<
 /// - `Ok(())`: 初始化成功
 /// - `Err(Box<dyn std::error::Error>)`: 初始化失败
 pub fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    // 设备 id 需要在日志写入日志目录之前加载，用于判断是否是旧版本的已有安装
+    let device_id_result = device_id::init(app.handle());
+
+    // 最先初始化日志，后续各模块的日志才能写入文件
+    // 日志初始化失败不影响应用运行
+    match logging::init(app.handle()) {
+        Ok(log_control) => {
+            app.manage(log_control);
+        }
+        Err(e) => eprintln!("初始化日志失败: {}", e),
+    }
+    match device_id_result {
+        Ok(Some(source)) => tracing::info!(source = ?source, "已生成设备 id"),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "加载设备 id 失败，本次运行使用临时设备 id"),
+    }
+
+    // 服务端接口地址配置，日志上传等模块使用
+    app.manage(endpoints::Endpoints::load(app.handle()));
+
+    // 崩溃报告依赖日志目录中的最近日志，在日志之后初始化
+    if let Err(e) = crash::init(app.handle()) {
+        tracing::warn!(error = %e, "初始化崩溃报告失败");
+    }
+
+    // 应用设置，其他模块在初始化和运行中读取
+    let settings_store = settings::SettingsStore::new(app.handle())?;
+    app.manage(settings_store);
+    settings::init(app.handle());
+
     // 创建系统托盘图标
     tray::create_tray_icon(app.handle())?;
 
+    // 用户资料缓存依赖应用缓存目录，需要在 setup 阶段创建
+    let profile_cache = profiles::ProfileCache::new(app.handle())?;
+    app.manage(profile_cache);
+
+    // 媒体缓存同样依赖应用缓存目录
+    let media_cache = media_cache::MediaCache::new(app.handle())?;
+    app.manage(media_cache);
+
+    // 系统公告存储，加载后恢复失效定时器和未确认的公告弹窗
+    let notice_store = notices::NoticeStore::new(app.handle())?;
+    app.manage(notice_store);
+    notices::restore(app.handle())?;
+
+    // 记事本提醒存储，加载后启动调度任务并补发错过的提醒
+    let reminder_store = reminders::ReminderStore::new(app.handle())?;
+    app.manage(reminder_store);
+    reminders::start(app.handle());
+
+    // 应用状态上报的检查列表
+    let monitored_apps = apps_upload::MonitoredApps::new(app.handle())?;
+    app.manage(monitored_apps);
+
+    // 语音播报，播报线程需要应用句柄发送事件
+    let voice = voice_broadcast::VoiceBroadcast::new(
+        app.handle().clone(),
+        voice_broadcast::default_backend(),
+    );
+    app.manage(voice);
+
     // 设置 macOS Dock 点击事件处理
     #[cfg(target_os = "macos")]
     dock::setup_dock_event_handler(app)?;
@@ -61,6 +129,14 @@ pub fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>>
     // 监听窗口事件
     setup_window_events(app);
 
+    // 聊天弹出窗口，加载后恢复上次退出时打开的窗口
+    let chat_windows = chat_windows::ChatWindows::new(app.handle())?;
+    app.manage(chat_windows);
+    chat_windows::restore(app.handle())?;
+
+    // 处理首次启动参数中的深度链接，放在最后以便公告和弹出窗口已经就绪
+    deep_link::handle_args(app.handle(), std::env::args());
+
     Ok(())
 }
 
@@ -88,7 +164,12 @@ pub fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent)
 /// - `app`: Tauri 应用实例
 fn setup_window_events(app: &tauri::App) {
     // 监听主窗口的事件
-    if let Some(window) = app.get_webview_window("main") {
+    if let Some(window) = window::main_window(app.handle()) {
+        // 主窗口由配置文件创建，这里补充记录到窗口注册表
+        window::track_window(app.handle(), &window, window::WindowSpec::main());
+        // window-state 插件已经恢复了上次的位置，检查是否还在屏幕内
+        window::ensure_on_screen(&window);
+
         // 克隆 app_handle 以便在闭包中使用
         let app_handle = app.handle().clone();
 
@@ -101,8 +182,16 @@ fn setup_window_events(app: &tauri::App) {
                     // 阻止窗口真正关闭
                     api.prevent_close();
 
-                    // 只隐藏窗口，而不是真正关闭它
-                    window::hide_main_window(&app_handle);
+                    // 窗口不会真正关闭，window-state 插件不会在这里保存状态，需要手动保存
+                    window::save_window_state(&app_handle);
+
+                    // 按用户设置隐藏到托盘、最小化或退出
+                    lifecycle::handle_close_requested(&app_handle);
+                }
+                // 通知没有点击回调的平台上，点击通知激活应用后打开最近通知的会话
+                #[cfg(not(target_os = "linux"))]
+                tauri::WindowEvent::Focused(true) => {
+                    crate::notifications::open_notified_conversation(&app_handle);
                 }
                 _ => {}
             }
@@ -0,0 +1,484 @@
+/// 应用状态上报模块
+///
+/// 运维后台通过 EventAppsUpload 检查设备上是否安装并运行了必需的业务应用：
+/// - 需要检查的应用列表保存在应用数据目录的 apps_upload.json 中，
+///   可以由前端设置，也可以随服务端下发的 EventAppsUpload 更新
+/// - 已安装：在 XDG 应用目录中扫描 .desktop 文件，按名称、文件名或启动命令匹配
+/// - 运行中：扫描 /proc 中的进程，按进程名、启动命令或可执行文件名匹配
+///
+/// 目前只支持 Linux，其他平台上所有应用都报告为未安装、未运行
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+
+use crate::pb::*;
+use crate::utils::{decode_base64_message, AppResult};
+
+/// 检查列表存储文件名
+const APPS_FILE: &str = "apps_upload.json";
+
+/// 需要检查的应用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitoredApp {
+    /// 应用名称，与 .desktop 文件中的 Name 或文件名匹配
+    pub name: String,
+    /// 进程名，为空时使用 .desktop 文件的启动命令或应用名称匹配进程
+    #[serde(default)]
+    pub process_name: String,
+}
+
+impl From<&AppStatus> for MonitoredApp {
+    fn from(status: &AppStatus) -> Self {
+        Self {
+            name: status.name.clone(),
+            process_name: status.process_name.clone(),
+        }
+    }
+}
+
+/// 应用检查列表
+pub struct MonitoredApps {
+    /// 存储文件路径
+    path: PathBuf,
+    /// 需要检查的应用
+    apps: Mutex<Vec<MonitoredApp>>,
+}
+
+impl MonitoredApps {
+    /// 创建检查列表并从磁盘加载
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用数据目录
+    pub fn new(app: &tauri::AppHandle) -> AppResult<Self> {
+        let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(APPS_FILE);
+
+        let apps: Vec<MonitoredApp> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            apps: Mutex::new(apps),
+        })
+    }
+
+    /// 获取检查列表
+    pub fn list(&self) -> AppResult<Vec<MonitoredApp>> {
+        self.apps
+            .lock()
+            .map(|apps| apps.clone())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 替换检查列表并保存，忽略名称为空的应用
+    pub fn set(&self, apps: Vec<MonitoredApp>) -> AppResult<Vec<MonitoredApp>> {
+        let apps: Vec<MonitoredApp> = apps
+            .into_iter()
+            .filter(|app| !app.name.trim().is_empty())
+            .collect();
+        let json = serde_json::to_string_pretty(&apps).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| e.to_string())?;
+        *self.apps.lock().map_err(|e| e.to_string())? = apps.clone();
+        Ok(apps)
+    }
+}
+
+/// .desktop 文件中与匹配相关的内容
+#[derive(Debug, Default)]
+struct DesktopEntry {
+    /// 文件名（去掉 .desktop 后缀），如 org.gnome.Calculator
+    id: String,
+    /// Name 以及各语言的 Name[xx]
+    names: Vec<String>,
+    /// 启动命令中可执行文件的文件名
+    exec: Option<String>,
+    /// Hidden=true 表示应用已被删除，同时隐藏其他目录中的同名文件
+    hidden: bool,
+}
+
+impl DesktopEntry {
+    /// 判断是否是指定的应用
+    fn matches(&self, name: &str) -> bool {
+        self.id.eq_ignore_ascii_case(name)
+            || self.names.iter().any(|n| n.eq_ignore_ascii_case(name))
+            || self
+                .exec
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(name))
+    }
+}
+
+/// 应用目录列表，按 XDG Base Directory 规范
+fn application_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut dirs: Vec<PathBuf> = data_home.into_iter().collect();
+    dirs.extend(data_dirs.split(':').map(PathBuf::from));
+    // flatpak 和 snap 安装的应用不一定在 XDG_DATA_DIRS 中
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+    if let Some(home) = &home {
+        dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    dirs.into_iter()
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// 取启动命令中可执行文件的文件名
+///
+/// 跳过 `env` 和 `KEY=VALUE` 形式的环境变量，如 `env FOO=1 /opt/app/bin/app %U` 得到 `app`
+fn exec_name(exec: &str) -> Option<String> {
+    exec.split_whitespace()
+        .map(|token| token.trim_matches(|c| c == '"' || c == '\''))
+        .find(|token| *token != "env" && !token.contains('='))
+        .and_then(|program| Path::new(program).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// 解析 .desktop 文件，只读取 [Desktop Entry] 段
+fn parse_desktop_entry(path: &Path) -> Option<DesktopEntry> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut entry = DesktopEntry {
+        id: path.file_stem()?.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    let mut in_main_section = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key == "Name" || (key.starts_with("Name[") && key.ends_with(']')) {
+            entry.names.push(value.to_string());
+        } else if key == "Exec" {
+            entry.exec = exec_name(value);
+        } else if key == "Hidden" {
+            entry.hidden = value.eq_ignore_ascii_case("true");
+        }
+    }
+    Some(entry)
+}
+
+/// 递归收集目录中的 .desktop 文件
+///
+/// 不进入指向目录的符号链接，避免链接成环时无限递归；
+/// flatpak 导出的 .desktop 文件本身是符号链接，按文件收集
+fn collect_desktop_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        // DirEntry::file_type 不跟随符号链接
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_desktop_files(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") && path.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+/// 扫描所有应用目录中的 .desktop 文件
+///
+/// 同一个文件名在多个目录中出现时，以排在前面的目录为准（与桌面环境一致），
+/// 因此用户目录中 Hidden=true 的文件可以隐藏系统目录中的应用
+fn scan_desktop_entries() -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for dir in application_dirs() {
+        let mut files = Vec::new();
+        collect_desktop_files(&dir, &mut files);
+        for path in files {
+            let Some(entry) = parse_desktop_entry(&path) else {
+                continue;
+            };
+            if seen.insert(entry.id.clone()) && !entry.hidden {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// 运行中的进程
+#[derive(Debug)]
+struct ProcessInfo {
+    /// /proc/<pid>/comm，内核最多保留 15 个字符
+    comm: String,
+    /// 启动命令第一个参数的文件名
+    argv0: Option<String>,
+    /// 可执行文件的文件名
+    exe: Option<String>,
+}
+
+impl ProcessInfo {
+    /// 判断进程是否是指定的程序，返回匹配到的进程名
+    fn matches(&self, program: &str) -> Option<String> {
+        // comm 会被截断，程序名较长时按前缀比较
+        let comm_matches = if program.len() > 15 {
+            program.starts_with(&self.comm) && self.comm.len() == 15
+        } else {
+            self.comm.eq_ignore_ascii_case(program)
+        };
+        if comm_matches
+            || self
+                .argv0
+                .as_deref()
+                .is_some_and(|a| a.eq_ignore_ascii_case(program))
+            || self
+                .exe
+                .as_deref()
+                .is_some_and(|e| e.eq_ignore_ascii_case(program))
+        {
+            Some(self.exe.clone().unwrap_or_else(|| self.comm.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+/// 扫描 /proc 中的进程
+///
+/// 没有权限读取的信息（如其他用户进程的 exe）会被忽略
+fn scan_processes() -> Vec<ProcessInfo> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let comm = std::fs::read_to_string(dir.join("comm")).ok()?;
+            let argv0 = std::fs::read(dir.join("cmdline")).ok().and_then(|cmdline| {
+                let first = cmdline.split(|b| *b == 0).next()?;
+                file_name(Path::new(&*String::from_utf8_lossy(first)))
+            });
+            let exe = std::fs::read_link(dir.join("exe"))
+                .ok()
+                .and_then(|exe| file_name(&exe));
+            Some(ProcessInfo {
+                comm: comm.trim().to_string(),
+                argv0,
+                exe,
+            })
+        })
+        .collect()
+}
+
+/// 检查应用的安装和运行状态
+fn collect_statuses(apps: &[MonitoredApp]) -> EventAppsUpload {
+    if !cfg!(target_os = "linux") {
+        tracing::warn!("当前平台不支持检查应用状态");
+    }
+    let (entries, processes) = if cfg!(target_os = "linux") {
+        (scan_desktop_entries(), scan_processes())
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let apps = apps
+        .iter()
+        .map(|app| {
+            let entry = entries.iter().find(|entry| entry.matches(&app.name));
+
+            // 依次用配置的进程名、.desktop 的启动命令和应用名称匹配进程
+            let programs: Vec<&str> = [
+                Some(app.process_name.as_str()),
+                entry.and_then(|entry| entry.exec.as_deref()),
+                Some(app.name.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|program| !program.is_empty())
+            .collect();
+            let running = programs.iter().find_map(|program| {
+                processes
+                    .iter()
+                    .find_map(|process| process.matches(program))
+            });
+
+            let process_name = running
+                .clone()
+                .or_else(|| entry.and_then(|entry| entry.exec.clone()))
+                .unwrap_or_else(|| app.process_name.clone());
+            AppStatus {
+                name: app.name.clone(),
+                installed: entry.is_some(),
+                running: running.is_some(),
+                process_name,
+                image: String::new(),
+            }
+        })
+        .collect();
+
+    EventAppsUpload { apps }
+}
+
+/// 生成应用状态上报消息
+///
+/// 服务端下发 EventAppsUpload 时，以其中的应用替换检查列表；
+/// 不传时使用已保存的检查列表
+///
+/// # 参数
+/// - `data`: base64 编码的 EventAppsUpload，可选
+///
+/// # 返回值
+/// - base64 编码的 EventAppsUpload，由前端发送给服务端
+#[tauri::command]
+pub async fn apply_apps_upload(
+    state: State<'_, MonitoredApps>,
+    data: Option<String>,
+) -> AppResult<String> {
+    let apps = match data {
+        Some(data) => {
+            let event: EventAppsUpload = decode_base64_message(&data)?;
+            state.set(event.apps.iter().map(MonitoredApp::from).collect())?
+        }
+        None => state.list()?,
+    };
+
+    // 扫描文件系统是阻塞操作，放到阻塞线程池中执行
+    let event = tauri::async_runtime::spawn_blocking(move || collect_statuses(&apps))
+        .await
+        .map_err(|e| e.to_string())?;
+    tracing::info!(
+        total = event.apps.len(),
+        installed = event.apps.iter().filter(|app| app.installed).count(),
+        running = event.apps.iter().filter(|app| app.running).count(),
+        "应用状态检查完成"
+    );
+    Ok(general_purpose::STANDARD.encode(encode_message(&event)))
+}
+
+/// 获取需要检查的应用列表
+#[tauri::command]
+pub fn get_monitored_apps(state: State<MonitoredApps>) -> AppResult<Vec<MonitoredApp>> {
+    state.list()
+}
+
+/// 设置需要检查的应用列表
+///
+/// # 参数
+/// - `apps`: 应用列表
+#[tauri::command]
+pub fn set_monitored_apps(
+    state: State<MonitoredApps>,
+    apps: Vec<MonitoredApp>,
+) -> AppResult<Vec<MonitoredApp>> {
+    state.set(apps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的临时目录，每个测试使用不同的名称
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("apps_upload-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn exec_name_skips_env_and_arguments() {
+        assert_eq!(exec_name("/opt/app/bin/app %U"), Some("app".to_string()));
+        assert_eq!(
+            exec_name("env FOO=1 BAR=2 /opt/app/bin/app %U"),
+            Some("app".to_string())
+        );
+        assert_eq!(
+            exec_name("'/usr/bin/code' --new-window"),
+            Some("code".to_string())
+        );
+        assert_eq!(exec_name("firefox"), Some("firefox".to_string()));
+        assert_eq!(exec_name(""), None);
+        assert_eq!(exec_name("env FOO=1"), None);
+    }
+
+    #[test]
+    fn parses_main_section_of_desktop_entry() {
+        let dir = test_dir("parse");
+        let path = dir.join("org.demo.App.desktop");
+        std::fs::write(
+            &path,
+            "# comment\n\
+             [Desktop Entry]\n\
+             Type=Application\n\
+             Name=Demo\n\
+             Name[zh_CN] = 演示\n\
+             Exec=env GDK_BACKEND=x11 /opt/demo/demo-bin %U\n\
+             Hidden=True\n\
+             [Desktop Action new-window]\n\
+             Name=New Window\n\
+             Exec=/opt/demo/other --new-window\n",
+        )
+        .unwrap();
+
+        let entry = parse_desktop_entry(&path).unwrap();
+        assert_eq!(entry.id, "org.demo.App");
+        assert_eq!(entry.names, vec!["Demo".to_string(), "演示".to_string()]);
+        assert_eq!(entry.exec, Some("demo-bin".to_string()));
+        assert!(entry.hidden);
+
+        assert!(parse_desktop_entry(&dir.join("missing.desktop")).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn does_not_follow_directory_symlinks() {
+        let dir = test_dir("walk");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub/app.desktop"), "[Desktop Entry]\nName=App\n").unwrap();
+        std::fs::write(dir.join("readme.txt"), "").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(dir.join("sub/app.desktop"), dir.join("link.desktop")).unwrap();
+
+        let mut files = Vec::new();
+        collect_desktop_files(&dir, &mut files);
+        files.sort();
+        assert_eq!(
+            files,
+            vec![dir.join("link.desktop"), dir.join("sub/app.desktop")]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
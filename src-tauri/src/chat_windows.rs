@@ -0,0 +1,319 @@
+/// 聊天弹出窗口模块
+///
+/// 用户可以把单个会话弹出到独立窗口，固定在其他应用旁边：
+/// - 每个会话（target_id + is_room）最多一个窗口，重复打开时聚焦已有窗口
+/// - 消息推送中属于该会话的消息只发送给对应的窗口（"chat-messages" 事件）
+/// - 窗口没有焦点时累计该会话的未读数，只发送给对应的窗口（"chat-unread-changed" 事件），
+///   窗口获得焦点时清零
+/// - 打开的弹出窗口保存在应用数据目录的 chat_windows.json 中，下次启动时恢复
+///
+/// 用户关闭窗口时从列表中移除；退出应用时窗口随应用销毁，仍保留在列表中
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+
+use crate::error::AppError;
+use crate::notifications::ConversationKey;
+use crate::pb::*;
+use crate::window::{self, WindowSpec};
+
+/// 弹出窗口列表存储文件名
+const CHAT_WINDOWS_FILE: &str = "chat_windows.json";
+
+/// 弹出的聊天窗口
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatWindow {
+    /// 用户 id 或群 id
+    pub target_id: u64,
+    /// 是否为群聊
+    pub is_room: bool,
+    /// 窗口标题
+    pub title: String,
+}
+
+impl ChatWindow {
+    /// 窗口所属的会话
+    pub fn key(&self) -> ConversationKey {
+        ConversationKey {
+            target_id: self.target_id,
+            is_room: self.is_room,
+        }
+    }
+}
+
+/// 弹出窗口的未读数变化
+#[derive(Debug, Clone, Serialize)]
+struct ChatUnreadChanged {
+    target_id: u64,
+    is_room: bool,
+    count: u32,
+}
+
+/// 聊天窗口标签
+fn label_of(key: &ConversationKey) -> String {
+    window::chat_window_label(key.target_id, key.is_room)
+}
+
+/// 弹出窗口状态
+pub struct ChatWindows {
+    /// 存储文件路径
+    path: PathBuf,
+    /// 打开的弹出窗口，按打开顺序排列
+    windows: Mutex<Vec<ChatWindow>>,
+    /// 每个弹出窗口的未读数
+    unread: Mutex<HashMap<ConversationKey, u32>>,
+}
+
+impl ChatWindows {
+    /// 创建弹出窗口状态并从磁盘加载上次打开的窗口
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用数据目录
+    pub fn new(app: &tauri::AppHandle) -> Result<Self, AppError> {
+        let dir = app.path().app_data_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(CHAT_WINDOWS_FILE);
+
+        let windows: Vec<ChatWindow> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            windows: Mutex::new(windows),
+            unread: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 获取打开的弹出窗口
+    pub fn list(&self) -> Result<Vec<ChatWindow>, AppError> {
+        Ok(self.windows.lock()?.clone())
+    }
+
+    /// 记录弹出窗口已打开，同一会话只保留一条并更新标题
+    fn insert(&self, chat: ChatWindow) -> Result<(), AppError> {
+        let mut windows = self.windows.lock()?;
+        match windows.iter_mut().find(|w| w.key() == chat.key()) {
+            Some(existing) => *existing = chat,
+            None => windows.push(chat),
+        }
+        self.save(&windows)
+    }
+
+    /// 记录弹出窗口已被用户关闭
+    fn remove(&self, key: &ConversationKey) -> Result<(), AppError> {
+        let mut windows = self.windows.lock()?;
+        windows.retain(|w| w.key() != *key);
+        self.unread.lock()?.remove(key);
+        self.save(&windows)
+    }
+
+    /// 写入存储文件
+    fn save(&self, windows: &[ChatWindow]) -> Result<(), AppError> {
+        let json =
+            serde_json::to_string_pretty(windows).map_err(|e| AppError::Internal(e.to_string()))?;
+        std::fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    /// 增加会话的未读数，返回新的未读数
+    fn add_unread(&self, key: ConversationKey, count: u32) -> Result<u32, AppError> {
+        let mut unread = self.unread.lock()?;
+        let total = unread.entry(key).or_insert(0);
+        *total += count;
+        Ok(*total)
+    }
+
+    /// 清零会话的未读数，返回清零前是否有未读
+    fn clear_unread(&self, key: &ConversationKey) -> Result<bool, AppError> {
+        Ok(self
+            .unread
+            .lock()?
+            .remove(key)
+            .is_some_and(|count| count > 0))
+    }
+
+    /// 获取会话的未读数
+    pub fn unread(&self, key: &ConversationKey) -> Result<u32, AppError> {
+        Ok(self.unread.lock()?.get(key).copied().unwrap_or(0))
+    }
+}
+
+/// 向弹出窗口发送未读数变化事件
+fn emit_unread_changed(app: &tauri::AppHandle, key: ConversationKey, count: u32) {
+    let payload = ChatUnreadChanged {
+        target_id: key.target_id,
+        is_room: key.is_room,
+        count,
+    };
+    let _ = app.emit_to(label_of(&key), "chat-unread-changed", payload);
+}
+
+/// 清零弹出窗口的未读数，有变化时通知窗口
+fn mark_read(app: &tauri::AppHandle, key: ConversationKey) -> Result<(), AppError> {
+    if app.state::<ChatWindows>().clear_unread(&key)? {
+        emit_unread_changed(app, key, 0);
+    }
+    Ok(())
+}
+
+/// 打开会话的弹出窗口，已打开时聚焦
+///
+/// 在 Windows 上同步命令中创建窗口会死锁，调用这个函数的命令必须是 async
+pub fn open(app: &tauri::AppHandle, chat: ChatWindow) -> Result<(), AppError> {
+    let key = chat.key();
+    let spec = WindowSpec::chat(chat.target_id, chat.is_room, &chat.title);
+    let (webview, created) = window::open_window(app, spec)?;
+    if created {
+        let app_handle = app.clone();
+        webview.on_window_event(move |event| match event {
+            // 只有用户主动关闭才从列表中移除，退出应用时不会触发 CloseRequested
+            tauri::WindowEvent::CloseRequested { .. } => {
+                if let Err(e) = app_handle.state::<ChatWindows>().remove(&key) {
+                    tracing::warn!(error = %e, "移除聊天弹出窗口记录失败");
+                }
+            }
+            tauri::WindowEvent::Focused(true) => {
+                let _ = mark_read(&app_handle, key);
+            }
+            _ => {}
+        });
+    } else {
+        let _ = webview.set_title(&chat.title);
+    }
+    app.state::<ChatWindows>().insert(chat)
+}
+
+/// 恢复上次退出时打开的弹出窗口
+///
+/// 在 setup 阶段调用，单个窗口恢复失败不影响其他窗口
+pub fn restore(app: &tauri::AppHandle) -> Result<(), AppError> {
+    for chat in app.state::<ChatWindows>().list()? {
+        tracing::debug!(
+            target_id = chat.target_id,
+            is_room = chat.is_room,
+            "恢复聊天弹出窗口"
+        );
+        if let Err(e) = open(app, chat) {
+            tracing::warn!(error = %e, "恢复聊天弹出窗口失败");
+        }
+    }
+    Ok(())
+}
+
+/// 判断会话的弹出窗口是否可见且有焦点
+///
+/// 此时用户正在弹出窗口中查看该会话，不需要再弹系统通知
+pub fn is_focused(app: &tauri::AppHandle, key: &ConversationKey) -> bool {
+    app.get_webview_window(&label_of(key))
+        .is_some_and(|webview| {
+            webview.is_visible().unwrap_or(false) && webview.is_focused().unwrap_or(false)
+        })
+}
+
+/// 把消息推送中属于已弹出会话的消息发送给对应的窗口
+///
+/// 每个窗口只收到自己会话的消息（base64 编码的 MessagePush），
+/// 窗口没有焦点时按其中别人发来的、未撤回的消息累计未读数
+///
+/// # 参数
+/// - `push`: 消息推送
+/// - `current_user_id`: 当前登录用户的 id，自己发出的消息不计入未读
+pub fn route_messages(
+    app: &tauri::AppHandle,
+    push: &MessagePush,
+    current_user_id: u64,
+) -> Result<(), AppError> {
+    let Some(state) = app.try_state::<ChatWindows>() else {
+        return Ok(());
+    };
+
+    // 按会话分组，保持消息原有顺序
+    let mut groups: Vec<(ConversationKey, Vec<MessagePushItem>)> = Vec::new();
+    for item in &push.messages {
+        let key = ConversationKey::of_message(item);
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, items)) => items.push(item.clone()),
+            None => groups.push((key, vec![item.clone()])),
+        }
+    }
+
+    for (key, messages) in groups {
+        let Some(webview) = app.get_webview_window(&label_of(&key)) else {
+            continue;
+        };
+
+        let unread = messages
+            .iter()
+            .filter(|item| item.from_id != current_user_id && item.cancelled_by == 0)
+            .count() as u32;
+        let routed = MessagePush {
+            msg_ids: messages.iter().map(|item| item.id).collect(),
+            messages,
+        };
+        let data = general_purpose::STANDARD.encode(encode_message(&routed));
+        app.emit_to(label_of(&key), "chat-messages", data)?;
+
+        if unread > 0 && !webview.is_focused().unwrap_or(false) {
+            let count = state.add_unread(key, unread)?;
+            emit_unread_changed(app, key, count);
+        }
+    }
+    Ok(())
+}
+
+/// 打开会话的弹出窗口，同一会话只会打开一个窗口
+///
+/// # 参数
+/// - `target_id`: 用户 id 或群 id
+/// - `is_room`: 是否为群聊
+/// - `title`: 窗口标题，一般是对方昵称或群名称
+#[tauri::command]
+pub async fn open_chat_window(
+    app: tauri::AppHandle,
+    target_id: u64,
+    is_room: bool,
+    title: String,
+) -> Result<(), AppError> {
+    open(
+        &app,
+        ChatWindow {
+            target_id,
+            is_room,
+            title,
+        },
+    )
+}
+
+/// 获取打开的弹出窗口
+#[tauri::command]
+pub fn list_chat_windows(state: State<ChatWindows>) -> Result<Vec<ChatWindow>, AppError> {
+    state.list()
+}
+
+/// 获取弹出窗口的未读数
+#[tauri::command]
+pub fn get_chat_window_unread(
+    state: State<ChatWindows>,
+    target_id: u64,
+    is_room: bool,
+) -> Result<u32, AppError> {
+    state.unread(&ConversationKey { target_id, is_room })
+}
+
+/// 清零弹出窗口的未读数
+///
+/// 窗口获得焦点时会自动清零，前端也可以在用户滚动到底部等时机主动调用
+#[tauri::command]
+pub fn mark_chat_window_read(
+    app: tauri::AppHandle,
+    target_id: u64,
+    is_room: bool,
+) -> Result<(), AppError> {
+    mark_read(&app, ConversationKey { target_id, is_room })
+}
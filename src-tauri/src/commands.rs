@@ -1,6 +1,6 @@
 use tauri::State;
 
-use crate::{tray, unread_count::UnreadCount, pb::*};
+use crate::{error::AppError, notices, tray, unread_count::UnreadCount, voice_broadcast, pb::*};
 use base64::{Engine as _, engine::general_purpose};
 
 /// Tauri 命令处理模块
@@ -38,14 +38,14 @@ pub fn greet(name: &str) -> String {
 ///
 /// # 返回值
 /// - `Ok(u32)`: 操作成功，返回新的未读数
-/// - `Err(String)`: 操作失败，返回错误信息
+/// - `Err(AppError)`: 未读数更新失败，返回错误码和错误信息；托盘等 UI 只是尽力更新，不会导致失败
 #[tauri::command]
-pub fn increment_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Result<u32, String> {
+pub fn increment_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Result<u32, AppError> {
     // 增加未读数
     let new_count = state.increment()?;
 
     // 更新托盘图标标题显示未读数
-    tray::update_tray_title(&app, new_count)?;
+    tray::update_tray_title(&app, new_count);
 
     Ok(new_count)
 }
@@ -57,9 +57,9 @@ pub fn increment_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Res
 ///
 /// # 返回值
 /// - `Ok(u32)`: 操作成功，返回当前未读数
-/// - `Err(String)`: 操作失败，返回错误信息
+/// - `Err(AppError)`: 操作失败，返回错误码和错误信息
 #[tauri::command]
-pub fn get_unread_count(state: State<UnreadCount>) -> Result<u32, String> {
+pub fn get_unread_count(state: State<UnreadCount>) -> Result<u32, AppError> {
     state.get()
 }
 
@@ -71,14 +71,14 @@ pub fn get_unread_count(state: State<UnreadCount>) -> Result<u32, String> {
 ///
 /// # 返回值
 /// - `Ok(u32)`: 操作成功，返回新的未读数（应该是0）
-/// - `Err(String)`: 操作失败，返回错误信息
+/// - `Err(AppError)`: 未读数清除失败，返回错误码和错误信息；托盘等 UI 只是尽力更新，不会导致失败
 #[tauri::command]
-pub fn clear_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Result<u32, String> {
+pub fn clear_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Result<u32, AppError> {
     // 清除未读数
     let new_count = state.clear()?;
 
     // 更新托盘图标标题和其他 UI 元素，显示没有未读消息
-    tray::update_tray_title(&app, new_count)?;
+    tray::update_tray_title(&app, new_count);
 
     Ok(new_count)
 }
@@ -92,7 +92,7 @@ pub fn clear_unread(state: State<UnreadCount>, app: tauri::AppHandle) -> Result<
 /// # 返回值
 /// - 序列化后的字节数组（base64 编码）
 #[tauri::command]
-pub fn create_event_message(event_type: i32, data: String) -> Result<String, String> {
+pub fn create_event_message(event_type: i32, data: String) -> Result<String, AppError> {
     let event = EventCommon {
         r#type: event_type,
         data,
@@ -110,9 +110,9 @@ pub fn create_event_message(event_type: i32, data: String) -> Result<String, Str
 /// # 返回值
 /// - 解析后的事件对象（JSON 格式）
 #[tauri::command]
-pub fn parse_event_message(data: String) -> Result<serde_json::Value, String> {
-    let bytes = general_purpose::STANDARD.decode(data).map_err(|e| format!("Base64 decode error: {}", e))?;
-    let event = EventCommon::from_bytes(&bytes).map_err(|e| format!("Protobuf decode error: {}", e))?;
+pub fn parse_event_message(data: String) -> Result<serde_json::Value, AppError> {
+    let bytes = general_purpose::STANDARD.decode(data)?;
+    let event = EventCommon::from_bytes(&bytes)?;
     
     let json = serde_json::json!({
         "type": event.r#type,
@@ -140,7 +140,7 @@ pub fn create_message_send(
     to_id: u64,
     is_room: bool,
     meta: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let message = MessageSend {
         r#type: msg_type,
         content,
@@ -161,9 +161,9 @@ pub fn create_message_send(
 /// # 返回值
 /// - 解析后的消息对象（JSON 格式）
 #[tauri::command]
-pub fn parse_message_send(data: String) -> Result<serde_json::Value, String> {
-    let bytes = general_purpose::STANDARD.decode(data).map_err(|e| format!("Base64 decode error: {}", e))?;
-    let message = MessageSend::from_bytes(&bytes).map_err(|e| format!("Protobuf decode error: {}", e))?;
+pub fn parse_message_send(data: String) -> Result<serde_json::Value, AppError> {
+    let bytes = general_purpose::STANDARD.decode(data)?;
+    let message = MessageSend::from_bytes(&bytes)?;
     
     let json = serde_json::json!({
         "type": message.r#type,
@@ -175,3 +175,25 @@ pub fn parse_message_send(data: String) -> Result<serde_json::Value, String> {
     
     Ok(json)
 }
+
+/// 处理服务端推送的公共事件
+///
+/// 根据 EventCommon 的类型分发到对应的模块：
+/// - 1: 删除系统公告，data 形如 `{"notice_id": 100}`
+/// - 2: 语音播报，data 形如 `{"text": "..."}`
+///
+/// 其他类型暂不在 Rust 侧处理，由前端自行解析
+///
+/// # 参数
+/// - `data`: base64 编码的 EventCommon
+#[tauri::command]
+pub async fn apply_event_common(app: tauri::AppHandle, data: String) -> Result<(), AppError> {
+    let event: EventCommon = crate::utils::decode_base64_message(&data)?;
+
+    match event.r#type {
+        1 => notices::handle_delete_event(&app, &event.data)?,
+        2 => voice_broadcast::handle_broadcast_event(&app, &event.data)?,
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,333 @@
+/// 崩溃报告模块
+///
+/// 安装 panic 钩子，在程序 panic 时把崩溃现场写入应用数据目录的 crash_reports 目录：
+/// - panic 信息、位置、线程名和完整的调用栈
+/// - 应用版本、操作系统、设备 id（与 get_device_info 相同）
+/// - 最近的若干行日志（取自日志模块在内存中保留的最近日志，不再读日志文件）
+/// - 前端上报的连接状态
+///
+/// 应用构建完成之前（如 `.expect("error while building tauri application")`）发生的 panic
+/// 先写入系统临时目录，初始化时再移动到崩溃报告目录
+///
+/// panic 钩子先写报告再记录日志，写报告的过程中不调用 tracing，避免在日志相关的锁上死锁或再次 panic
+///
+/// 下次启动时如果存在未提交的崩溃报告，会弹窗询问用户是否提交；
+/// 提交地址只来自接口地址配置（见 endpoints 模块），未配置时只保留在本地，
+/// 前端也可以通过命令列出、提交或丢弃这些报告
+use std::backtrace::Backtrace;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::device_id;
+use crate::endpoints::Endpoints;
+use crate::logging;
+use crate::shutdown;
+use crate::utils::{now_millis, AppResult};
+
+/// 崩溃报告目录名，位于应用数据目录下
+const CRASH_DIR_NAME: &str = "crash_reports";
+
+/// 初始化之前的崩溃报告所在的临时目录名
+const FALLBACK_DIR_NAME: &str = "tauri-demo-crash_reports";
+
+/// 崩溃报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// 报告 id，也是文件名
+    pub id: String,
+    /// 崩溃时间（毫秒）
+    pub time: i64,
+    /// 应用版本
+    pub app_version: String,
+    /// 操作系统和架构
+    pub os: String,
+    /// 设备 id
+    pub device_id: String,
+    /// panic 所在线程
+    pub thread: String,
+    /// panic 信息
+    pub message: String,
+    /// panic 位置（文件:行:列）
+    pub location: Option<String>,
+    /// 调用栈
+    pub backtrace: String,
+    /// 前端上报的连接状态
+    pub connection_state: Option<String>,
+    /// 崩溃前最近的日志
+    pub recent_logs: Vec<String>,
+}
+
+/// 崩溃时需要的上下文
+///
+/// panic 钩子中不能依赖 Tauri 状态的完整性，所以在 setup 阶段提前准备好
+struct CrashContext {
+    dir: PathBuf,
+    device_id: String,
+}
+
+/// 崩溃上下文，`init` 之前发生的 panic 写入临时目录
+static CONTEXT: OnceLock<CrashContext> = OnceLock::new();
+
+/// 前端上报的连接状态
+static CONNECTION_STATE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 安装 panic 钩子
+///
+/// 在 `run()` 的最开始调用，保留默认钩子的输出（打印到标准错误）
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let context = CONTEXT.get();
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+        let report = CrashReport {
+            id: format!("crash-{}", now_millis()),
+            time: now_millis(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            device_id: match context {
+                Some(context) => context.device_id.clone(),
+                None => current_device_id(),
+            },
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string(),
+            message: message.clone(),
+            location: location.clone(),
+            backtrace: Backtrace::force_capture().to_string(),
+            connection_state: CONNECTION_STATE.lock().ok().and_then(|s| s.clone()),
+            recent_logs: logging::recent_lines(),
+        };
+        let dir = match context {
+            Some(context) => context.dir.clone(),
+            None => fallback_dir(),
+        };
+        if let Err(e) = write_report(&dir, &report) {
+            eprintln!("写入崩溃报告失败: {}", e);
+        }
+        tracing::error!(message = %message, location = ?location, "程序发生 panic");
+    }));
+}
+
+/// 获取设备 id，与 get_device_info 返回的相同
+fn current_device_id() -> String {
+    device_id::get_device_info()
+        .map(|info| info.device_id().to_string())
+        .unwrap_or_default()
+}
+
+/// 初始化之前的崩溃报告目录
+fn fallback_dir() -> PathBuf {
+    std::env::temp_dir().join(FALLBACK_DIR_NAME)
+}
+
+/// 写入崩溃报告
+fn write_report(dir: &Path, report: &CrashReport) -> AppResult<()> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(format!("{}.json", report.id)), json).map_err(|e| e.to_string())
+}
+
+/// 初始化崩溃上下文，并在存在未提交的报告时询问用户是否提交
+///
+/// 在 setup 阶段日志和接口地址配置初始化之后调用
+pub fn init(app: &tauri::AppHandle) -> AppResult<()> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(CRASH_DIR_NAME);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    adopt_fallback_reports(&dir);
+
+    let _ = CONTEXT.set(CrashContext {
+        dir: dir.clone(),
+        device_id: current_device_id(),
+    });
+    app.manage(CrashReports { dir });
+
+    offer_pending_reports(app);
+    Ok(())
+}
+
+/// 把初始化之前写入临时目录的崩溃报告移动到崩溃报告目录
+fn adopt_fallback_reports(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(fallback_dir()) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let dest = dir.join(entry.file_name());
+        // 临时目录可能与应用数据目录不在同一个文件系统上，rename 失败时复制后删除
+        if std::fs::rename(entry.path(), &dest).is_err()
+            && std::fs::copy(entry.path(), &dest).is_ok()
+        {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 崩溃报告存储
+pub struct CrashReports {
+    /// 崩溃报告目录
+    dir: PathBuf,
+}
+
+impl CrashReports {
+    /// 列出未提交的崩溃报告，按时间从旧到新排列
+    pub fn pending(&self) -> AppResult<Vec<CrashReport>> {
+        let mut reports: Vec<CrashReport> = std::fs::read_dir(&self.dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        reports.sort_by_key(|report| report.time);
+        Ok(reports)
+    }
+
+    /// 删除崩溃报告
+    pub fn remove(&self, id: &str) -> AppResult<()> {
+        // id 来自前端，只允许报告文件名中出现的字符
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid crash report id: {}", id));
+        }
+        let path = self.dir.join(format!("{}.json", id));
+        if path.is_file() {
+            std::fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// 逐个提交崩溃报告，提交成功的报告会被删除
+    ///
+    /// # 返回值
+    /// - `Ok(usize)`: 提交成功的报告数量
+    pub async fn submit(&self, url: &str) -> AppResult<usize> {
+        let client = tauri_plugin_http::reqwest::Client::new();
+        let mut submitted = 0;
+        for report in self.pending()? {
+            let response = client
+                .post(url)
+                .json(&report)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("HTTP {}", response.status()));
+            }
+            self.remove(&report.id)?;
+            submitted += 1;
+        }
+        Ok(submitted)
+    }
+}
+
+/// 启动时如果存在未提交的崩溃报告，弹窗询问用户是否提交
+///
+/// 未配置提交地址时报告只保留在本地
+fn offer_pending_reports(app: &tauri::AppHandle) {
+    let count = app
+        .state::<CrashReports>()
+        .pending()
+        .map(|reports| reports.len())
+        .unwrap_or(0);
+    if count == 0 {
+        return;
+    }
+    let Some(url) = app.state::<Endpoints>().crash_report.clone() else {
+        tracing::info!(count, "未配置崩溃报告提交地址，崩溃报告只保留在本地");
+        return;
+    };
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "应用上次运行时发生了 {} 次崩溃，是否提交崩溃报告帮助我们改进？",
+            count
+        ))
+        .title("崩溃报告")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "提交".to_string(),
+            "忽略".to_string(),
+        ))
+        .show(move |submit| {
+            if !submit {
+                return;
+            }
+            let handle = app_handle.clone();
+            shutdown::spawn(&handle, "crash_report_submit", async move {
+                let reports = app_handle.state::<CrashReports>();
+                match reports.submit(&url).await {
+                    Ok(n) => tracing::info!(count = n, "崩溃报告已提交"),
+                    Err(e) => tracing::warn!(error = %e, "提交崩溃报告失败"),
+                }
+            });
+        });
+}
+
+/// 上报当前的连接状态，崩溃时会写入报告
+///
+/// # 参数
+/// - `state`: 连接状态描述，如 connected、reconnecting
+#[tauri::command]
+pub fn set_connection_state(state: String) {
+    if let Ok(mut current) = CONNECTION_STATE.lock() {
+        *current = Some(state);
+    }
+}
+
+/// 列出未提交的崩溃报告
+#[tauri::command]
+pub fn list_crash_reports(state: State<CrashReports>) -> AppResult<Vec<CrashReport>> {
+    state.pending()
+}
+
+/// 提交所有未提交的崩溃报告
+///
+/// 提交地址只来自接口地址配置，未配置时返回错误
+#[tauri::command]
+pub async fn submit_crash_reports(
+    state: State<'_, CrashReports>,
+    endpoints: State<'_, Endpoints>,
+) -> AppResult<usize> {
+    let url = endpoints
+        .crash_report
+        .clone()
+        .ok_or_else(|| "Crash report url is not configured".to_string())?;
+    state.submit(&url).await
+}
+
+/// 丢弃崩溃报告
+///
+/// # 参数
+/// - `id`: 报告 id，不传时丢弃全部
+#[tauri::command]
+pub fn discard_crash_reports(state: State<CrashReports>, id: Option<String>) -> AppResult<()> {
+    match id {
+        Some(id) => state.remove(&id),
+        None => {
+            for report in state.pending()? {
+                state.remove(&report.id)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,358 @@
+/// 深度链接模块
+///
+/// 解析启动参数中的深度链接，打开会话、加入通话或打开公告。支持的格式：
+/// - `demo://chat/<room_id>`、`--open-conversation=<room_id>`: 打开群聊
+/// - `demo://chat/user/<user_id>`、`--open-conversation=user:<user_id>`: 打开私聊
+/// - `demo://call/<room_id>`、`--join-call=<room_id>`: 加入群通话
+/// - `demo://notice/<notice_id>`、`--open-notice=<notice_id>`: 打开系统公告
+///
+/// 首次启动的参数在 setup 阶段处理，再次启动时单实例插件把第二个实例的参数转给正在运行的实例，
+/// 两者使用同一个解析函数。无法识别的参数忽略，格式错误的链接记录日志后忽略。
+///
+/// 打开公告由 Rust 处理；会话已经弹出到独立窗口时聚焦该窗口；
+/// 其余链接显示主窗口并通过 "deep-link" 事件交给主窗口处理。
+/// 主窗口前端加载完成前收到的链接先保存，前端调用 `take_pending_deep_links` 取走。
+/// 主窗口页面重新加载时回到未就绪状态，新页面再次调用后才直接发送
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::webview::{PageLoadEvent, PageLoadPayload};
+use tauri::{Emitter, Manager, State, Url, Webview};
+
+use crate::error::AppError;
+use crate::notices;
+use crate::shutdown;
+use crate::window;
+
+/// 深度链接的 URL scheme
+pub const DEEP_LINK_SCHEME: &str = "demo";
+
+/// 单个参数的最大长度，超过的参数不解析
+const MAX_ARG_LEN: usize = 2048;
+
+/// 深度链接
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeepLink {
+    /// 打开会话
+    OpenConversation { target_id: u64, is_room: bool },
+    /// 加入群通话
+    JoinCall { room_id: u64 },
+    /// 打开系统公告
+    OpenNotice { notice_id: u64 },
+}
+
+/// 解析 id，必须是正整数
+fn parse_id(value: &str, what: &str) -> Result<u64, AppError> {
+    match value.parse::<u64>() {
+        Ok(id) if id > 0 => Ok(id),
+        _ => Err(AppError::InvalidDeepLink(format!(
+            "invalid {}: {}",
+            what, value
+        ))),
+    }
+}
+
+/// 解析会话 id，`user:<id>` 为私聊，否则为群聊
+fn parse_conversation(value: &str) -> Result<DeepLink, AppError> {
+    let (target_id, is_room) = match value.strip_prefix("user:") {
+        Some(user_id) => (parse_id(user_id, "user id")?, false),
+        None => (parse_id(value, "room id")?, true),
+    };
+    Ok(DeepLink::OpenConversation { target_id, is_room })
+}
+
+/// 解析 `demo://` 链接
+pub fn parse_url(url: &str) -> Result<DeepLink, AppError> {
+    let url = Url::parse(url).map_err(|e| AppError::InvalidDeepLink(e.to_string()))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(AppError::InvalidDeepLink(format!(
+            "unsupported scheme: {}",
+            url.scheme()
+        )));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    match (host, segments.as_slice()) {
+        ("chat", ["user", user_id]) => Ok(DeepLink::OpenConversation {
+            target_id: parse_id(user_id, "user id")?,
+            is_room: false,
+        }),
+        ("chat", [room_id]) => Ok(DeepLink::OpenConversation {
+            target_id: parse_id(room_id, "room id")?,
+            is_room: true,
+        }),
+        ("call", [room_id]) => Ok(DeepLink::JoinCall {
+            room_id: parse_id(room_id, "room id")?,
+        }),
+        ("notice", [notice_id]) => Ok(DeepLink::OpenNotice {
+            notice_id: parse_id(notice_id, "notice id")?,
+        }),
+        _ => Err(AppError::InvalidDeepLink(format!("unknown link: {}", url))),
+    }
+}
+
+/// 解析单个启动参数
+///
+/// # 返回值
+/// - `Ok(Some(DeepLink))`: 深度链接
+/// - `Ok(None)`: 不是深度链接，如系统附加的其他参数
+/// - `Err(AppError::InvalidDeepLink)`: 是深度链接但格式错误
+pub fn parse_arg(arg: &str) -> Result<Option<DeepLink>, AppError> {
+    if arg.len() > MAX_ARG_LEN {
+        return Err(AppError::InvalidDeepLink("argument too long".to_string()));
+    }
+
+    if arg.starts_with(&format!("{}:", DEEP_LINK_SCHEME)) {
+        return parse_url(arg).map(Some);
+    }
+    let Some((flag, value)) = arg.split_once('=') else {
+        return Ok(None);
+    };
+    match flag {
+        "--open-conversation" => parse_conversation(value).map(Some),
+        "--join-call" => Ok(Some(DeepLink::JoinCall {
+            room_id: parse_id(value, "room id")?,
+        })),
+        "--open-notice" => Ok(Some(DeepLink::OpenNotice {
+            notice_id: parse_id(value, "notice id")?,
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// 解析启动参数中的所有深度链接，第一个参数是可执行文件路径，跳过
+pub fn parse_args<I, S>(args: I) -> Vec<DeepLink>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    args.into_iter()
+        .skip(1)
+        .filter_map(|arg| match parse_arg(arg.as_ref()) {
+            Ok(link) => link,
+            Err(e) => {
+                tracing::warn!(error = %e, "忽略无效的深度链接");
+                None
+            }
+        })
+        .collect()
+}
+
+/// 深度链接状态
+#[derive(Default)]
+pub struct DeepLinks {
+    /// 主窗口前端加载完成前收到的链接
+    pending: Mutex<Vec<DeepLink>>,
+    /// 主窗口前端是否已经开始接收链接，页面重新加载时重置
+    ready: AtomicBool,
+}
+
+impl DeepLinks {
+    /// 创建深度链接状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// 把链接交给主窗口前端，前端尚未就绪时先保存
+fn deliver_to_main(app: &tauri::AppHandle, link: DeepLink) -> Result<(), AppError> {
+    let state = app.state::<DeepLinks>();
+    // 先持有锁再检查，避免与 take_pending_deep_links 交错导致链接丢失
+    let mut pending = state.pending.lock()?;
+    if state.ready.load(Ordering::SeqCst) {
+        app.emit_to(window::MAIN_WINDOW_LABEL, "deep-link", link)?;
+    } else {
+        pending.push(link);
+    }
+    Ok(())
+}
+
+/// 处理一个深度链接
+///
+/// 可能需要创建窗口，在 Windows 上不能在同步命令中调用
+pub fn dispatch(app: &tauri::AppHandle, link: DeepLink) -> Result<(), AppError> {
+    tracing::info!(link = ?link, "处理深度链接");
+    if let DeepLink::OpenNotice { notice_id } = link {
+        notices::show_notice(app, notice_id)?;
+        return Ok(());
+    }
+
+    if let DeepLink::OpenConversation { target_id, is_room } = link {
+        let label = window::chat_window_label(target_id, is_room);
+        if app.get_webview_window(&label).is_some() {
+            return window::show_window(app, &label);
+        }
+    }
+
+    window::show_main_window(app);
+    deliver_to_main(app, link)
+}
+
+/// 处理启动参数中的深度链接
+///
+/// 在 setup 阶段用首次启动的参数调用，单实例插件的回调中用第二个实例的参数调用
+pub fn handle_args<I, S>(app: &tauri::AppHandle, args: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let links = parse_args(args);
+    if links.is_empty() {
+        return;
+    }
+    let handle = app.clone();
+    shutdown::spawn(app, "deep_link", async move {
+        for link in links {
+            if let Err(e) = dispatch(&handle, link) {
+                tracing::warn!(error = %e, link = ?link, "处理深度链接失败");
+            }
+        }
+    });
+}
+
+/// 取走主窗口前端加载完成前收到的深度链接
+///
+/// 主窗口前端注册 "deep-link" 事件监听后调用，之后的链接直接通过事件发送
+#[tauri::command]
+pub fn take_pending_deep_links(state: State<DeepLinks>) -> Result<Vec<DeepLink>, AppError> {
+    let mut pending = state.pending.lock()?;
+    state.ready.store(true, Ordering::SeqCst);
+    Ok(std::mem::take(&mut *pending))
+}
+
+/// 页面加载事件处理器
+///
+/// 主窗口开始加载页面（首次加载或重新加载）时旧页面的事件监听已经失效，
+/// 回到未就绪状态，之后的链接先保存，等新页面调用 `take_pending_deep_links` 取走
+pub fn handle_page_load(webview: &Webview, payload: &PageLoadPayload<'_>) {
+    if webview.label() != window::MAIN_WINDOW_LABEL || payload.event() != PageLoadEvent::Started {
+        return;
+    }
+    let state = webview.state::<DeepLinks>();
+    // 与 deliver_to_main 使用同一把锁，避免检查就绪状态后才重置导致链接发给已失效的页面
+    let Ok(_pending) = state.pending.lock() else {
+        tracing::warn!("重置深度链接就绪状态失败");
+        return;
+    };
+    state.ready.store(false, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat(target_id: u64, is_room: bool) -> DeepLink {
+        DeepLink::OpenConversation { target_id, is_room }
+    }
+
+    #[test]
+    fn parses_valid_urls() {
+        assert_eq!(parse_url("demo://chat/42").unwrap(), chat(42, true));
+        assert_eq!(parse_url("demo://chat/user/7").unwrap(), chat(7, false));
+        assert_eq!(
+            parse_url("demo://call/5").unwrap(),
+            DeepLink::JoinCall { room_id: 5 }
+        );
+        assert_eq!(
+            parse_url("demo://notice/9").unwrap(),
+            DeepLink::OpenNotice { notice_id: 9 }
+        );
+    }
+
+    #[test]
+    fn ignores_trailing_slashes() {
+        assert_eq!(parse_url("demo://chat/42/").unwrap(), chat(42, true));
+        assert_eq!(parse_url("demo://chat/user/7//").unwrap(), chat(7, false));
+        assert_eq!(
+            parse_url("demo://call/5/").unwrap(),
+            DeepLink::JoinCall { room_id: 5 }
+        );
+    }
+
+    #[test]
+    fn rejects_zero_and_overflowing_ids() {
+        assert!(parse_url("demo://chat/0").is_err());
+        assert!(parse_url("demo://chat/user/0").is_err());
+        assert!(parse_url("demo://call/18446744073709551616").is_err());
+        assert!(parse_url("demo://notice/-1").is_err());
+        assert!(parse_url("demo://chat/abc").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_links() {
+        assert!(parse_url("demo://settings/1").is_err());
+        assert!(parse_url("demo://chat").is_err());
+        assert!(parse_url("demo://chat/1/2").is_err());
+        assert!(parse_url("demo://notice/user/1").is_err());
+        assert!(parse_url("https://chat/1").is_err());
+        assert!(parse_url("not a url").is_err());
+    }
+
+    #[test]
+    fn parses_args() {
+        assert_eq!(parse_arg("demo://chat/42").unwrap(), Some(chat(42, true)));
+        assert_eq!(
+            parse_arg("--open-conversation=12").unwrap(),
+            Some(chat(12, true))
+        );
+        assert_eq!(
+            parse_arg("--open-conversation=user:3").unwrap(),
+            Some(chat(3, false))
+        );
+        assert_eq!(
+            parse_arg("--join-call=8").unwrap(),
+            Some(DeepLink::JoinCall { room_id: 8 })
+        );
+        assert_eq!(
+            parse_arg("--open-notice=6").unwrap(),
+            Some(DeepLink::OpenNotice { notice_id: 6 })
+        );
+        assert_eq!(parse_arg("--autostart=true").unwrap(), None);
+        assert_eq!(parse_arg("--minimized").unwrap(), None);
+
+        assert!(parse_arg("--open-conversation=user:0").is_err());
+        assert!(parse_arg("--join-call=").is_err());
+        assert!(parse_arg("--open-notice=18446744073709551616").is_err());
+        assert!(parse_arg("demo://unknown/1").is_err());
+    }
+
+    #[test]
+    fn rejects_over_length_args() {
+        let padding = "0".repeat(MAX_ARG_LEN);
+        assert!(parse_arg(&format!("demo://chat/{}1", padding)).is_err());
+        assert!(parse_arg(&format!("--join-call={}1", padding)).is_err());
+        assert!(parse_arg(&format!("--other={}", padding)).is_err());
+
+        let longest = format!("--join-call={}1", &padding["--join-call=".len() + 1..]);
+        assert_eq!(longest.len(), MAX_ARG_LEN);
+        assert_eq!(
+            parse_arg(&longest).unwrap(),
+            Some(DeepLink::JoinCall { room_id: 1 })
+        );
+    }
+
+    #[test]
+    fn parse_args_skips_executable_and_invalid_args() {
+        let links = parse_args([
+            "demo://chat/1",
+            "--join-call=0",
+            "demo://call/2",
+            "--minimized",
+            "--open-notice=3",
+        ]);
+        assert_eq!(
+            links,
+            vec![
+                DeepLink::JoinCall { room_id: 2 },
+                DeepLink::OpenNotice { notice_id: 3 },
+            ]
+        );
+        assert!(parse_args(Vec::<String>::new()).is_empty());
+    }
+}
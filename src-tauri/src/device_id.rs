@@ -1,7 +1,98 @@
+/// 设备标识模块
+///
+/// 设备 id 首次生成后保存在应用数据目录的 device_id.json 中，之后一直使用保存的值，
+/// 不会因为修改主机名、启用 VPN 网卡或连接扩展坞而变化
+///
+/// 首次生成的来源：
+/// - Linux：基于 /etc/machine-id 生成（只使用其哈希，不暴露原始值）
+/// - 其他情况：随机生成
+///
+/// 旧版本的设备 id 由主机名和原始 MAC 地址直接推导，不再继续使用：
+/// 已有安装（包括已经保存了旧 id 的 device_id.json）会重新生成 id，
+/// 旧 id 只作为 `previous_device_id` 返回给前端，供服务端把旧 id 映射到新 id、避免用户被登出，
+/// 前端确认映射完成后调用 `confirm_device_id_migration` 删除
+///
+/// 是否是已有安装由应用数据目录中的安装标记文件判断，标记在每次启动时补写；
+/// 引入标记之前的旧版本没有写过标记，对它们退而检查日志目录中是否有旧版本写下的日志文件，
+/// 这一检查只在首次写入标记时进行一次
+///
+/// 设备指纹供服务端做风控参考，是以下信号加上每个安装随机生成的盐后的 SHA-256，
+/// 原始值不会离开本模块：
+/// - `mac_address`: 第一个网络接口的 MAC 地址
+/// - `hostname`: 系统主机名
+/// - `machine_id`: /etc/machine-id（仅 Linux）
+/// - `os`: 操作系统
+/// - `arch`: CPU 架构
+///
+/// 原始 MAC 地址只有在用户通过 `set_mac_address_exposure` 明确开启后才会返回给前端
+///
+/// `get_device_inventory` 汇总系统版本、硬件和网络等信息，用于诊断和登录时上报的设备元数据
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
 use mac_address::get_mac_address;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sysinfo::{MemoryRefreshKind, Networks, RefreshKind, System};
+use tauri::Manager;
 use uuid::Uuid;
 
+use crate::log_upload::LOG_DIR_NAME;
+use crate::utils::{now_millis, AppResult};
+
+/// 设备 id 文件名，位于应用数据目录下
+const DEVICE_ID_FILE: &str = "device_id.json";
+
+/// 安装标记文件名，位于应用数据目录下，存在即表示应用在这台设备上运行过
+const INSTALL_MARKER_FILE: &str = ".installed";
+
+/// 设备 id 的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceIdSource {
+    /// 旧版本由 MAC 地址和主机名推导的 id，只出现在旧的 device_id.json 中，加载时重新生成
+    Legacy,
+    /// 基于 /etc/machine-id 生成
+    MachineId,
+    /// 随机生成
+    Random,
+}
+
+/// 设备指纹使用的信号，按计算时的顺序排列
+pub const FINGERPRINT_SIGNALS: &[&str] = &["mac_address", "hostname", "machine_id", "os", "arch"];
+
+/// 持久化的设备 id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDeviceId {
+    /// 设备 id
+    device_id: String,
+    /// 生成来源
+    source: DeviceIdSource,
+    /// 生成时间（毫秒）
+    created_at: i64,
+    /// 计算设备指纹使用的盐，旧版本的文件中没有，加载时补上
+    #[serde(default)]
+    salt: String,
+    /// 是否允许把原始 MAC 地址返回给前端，默认不允许
+    #[serde(default)]
+    expose_mac_address: bool,
+    /// 迁移前的旧设备 id，服务端完成映射后删除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_device_id: Option<String>,
+}
+
+/// 已加载的设备 id 及其保存位置
+struct DeviceStore {
+    path: PathBuf,
+    stored: Mutex<StoredDeviceId>,
+}
+
+/// 设备 id 存储，`init` 之前为空
+static STORE: OnceLock<DeviceStore> = OnceLock::new();
+
+/// `init` 之前或加载失败时使用的临时设备 id，不保存
+static TEMPORARY: OnceLock<StoredDeviceId> = OnceLock::new();
+
 /// 设备信息结构体
 ///
 /// 这个结构体包含设备的唯一标识信息
@@ -10,54 +101,451 @@ use uuid::Uuid;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeviceInfo {
     /// 设备的 MAC 地址字符串表示
-    /// MAC 地址是网络接口的硬件地址，通常是唯一的
-    mac_address: String,
+    /// 只有用户开启 MAC 地址上报后才有值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mac_address: Option<String>,
 
-    /// 基于主机名和 MAC 地址生成的设备唯一标识符
-    /// 这是一个 UUID v5 格式的字符串，确保设备的唯一性
+    /// 稳定的设备唯一标识符（UUID 格式）
+    /// 首次生成后持久化保存，不随 MAC 地址和主机名变化
     device_id: String,
+
+    /// 设备 id 的来源，`init` 之前为空
+    source: Option<DeviceIdSource>,
+
+    /// 迁移前的旧设备 id，只在服务端完成映射之前返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_device_id: Option<String>,
+
+    /// 加盐哈希后的设备指纹，硬件或主机名变化时随之变化
+    fingerprint: String,
+
+    /// 计算设备指纹使用的信号名称
+    fingerprint_signals: Vec<String>,
 }
 
-/// 获取设备唯一标识信息
+impl DeviceInfo {
+    /// 获取设备唯一标识符
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+}
+
+/// 设备指纹的原始输入
+struct Fingerprint {
+    mac_address: String,
+    hostname: String,
+}
+
+impl Fingerprint {
+    /// 读取当前的 MAC 地址和主机名
+    fn current() -> Self {
+        // 尝试获取第一个MAC地址
+        // get_mac_address() 返回 Result<Option<MacAddress>, Error>
+        let mac_address = match get_mac_address() {
+            Ok(Some(ma)) => ma.to_string(), // 成功获取到 MAC 地址，转换为字符串
+            _ => "unknown".to_string(),     // 获取失败或没有 MAC 地址，使用默认值
+        };
+
+        // hostname::get() 获取系统主机名
+        let hostname = match hostname::get() {
+            Ok(h) => h.to_string_lossy().to_string(), // 成功获取主机名并转换为 UTF-8 字符串
+            Err(_) => "unknown-host".to_string(),     // 获取失败时使用默认值
+        };
+
+        Self {
+            mac_address,
+            hostname,
+        }
+    }
+
+    /// 加盐哈希后的设备指纹
+    ///
+    /// 每个信号以 `名称=值` 的形式逐行参与哈希，避免不同信号的值拼接后产生歧义
+    fn hash(&self, salt: &str) -> String {
+        let machine_id = raw_machine_id().unwrap_or_default();
+        let values = [
+            self.mac_address.as_str(),
+            self.hostname.as_str(),
+            machine_id.as_str(),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+        ];
+
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        for (name, value) in FINGERPRINT_SIGNALS.iter().zip(values) {
+            hasher.update(format!("\n{}={}", name, value).as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// 旧版本的设备 id 推导方式，只用于迁移时告诉服务端旧 id
+    ///
+    /// UUID v5 使用 SHA-1 哈希算法，基于命名空间和名称生成确定性的 UUID，
+    /// 相同的 MAC 地址和主机名总是生成相同的 id
+    fn legacy_id(&self) -> String {
+        Uuid::new_v5(
+            &device_namespace(),
+            format!("{}:{}", self.hostname, self.mac_address).as_bytes(),
+        )
+        .to_string()
+    }
+}
+
+/// 设备 id 使用的 UUID 命名空间
+fn device_namespace() -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_DNS, b"tauri.device.id")
+}
+
+/// 读取 /etc/machine-id 的原始值
+///
+/// machine-id 不应直接暴露给外部，只能用于哈希
+#[cfg(target_os = "linux")]
+fn raw_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn raw_machine_id() -> Option<String> {
+    None
+}
+
+/// 基于 /etc/machine-id 生成设备 id，使用以应用命名空间做的 UUID v5
+fn machine_id() -> Option<String> {
+    raw_machine_id().map(|id| Uuid::new_v5(&device_namespace(), id.as_bytes()).to_string())
+}
+
+/// 生成随机盐
+fn new_salt() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// 生成新的设备 id 及其来源
+fn new_device_id() -> (String, DeviceIdSource) {
+    match machine_id() {
+        Some(id) => (id, DeviceIdSource::MachineId),
+        None => (Uuid::new_v4().to_string(), DeviceIdSource::Random),
+    }
+}
+
+/// 生成新的设备 id
 ///
-/// 这个函数会收集设备的硬件和系统信息来生成唯一的设备标识
-/// 它会获取 MAC 地址和主机名，然后基于这些信息生成一个 UUID v5
-/// 这种方法确保了设备标识的唯一性和一致性
+/// # 参数
+/// - `previous_device_id`: 已有安装的旧设备 id，保留到服务端完成映射
+fn generate(previous_device_id: Option<String>) -> StoredDeviceId {
+    let (device_id, source) = new_device_id();
+    StoredDeviceId {
+        device_id,
+        source,
+        created_at: now_millis(),
+        salt: new_salt(),
+        expose_mac_address: false,
+        previous_device_id,
+    }
+}
+
+/// 读取保存的设备 id
+fn load(path: &Path) -> Option<StoredDeviceId> {
+    let json = std::fs::read_to_string(path).ok()?;
+    let stored: StoredDeviceId = serde_json::from_str(&json).ok()?;
+    Uuid::parse_str(&stored.device_id).ok().map(|_| stored)
+}
+
+/// 保存设备 id，先写临时文件再重命名，避免写入中断留下损坏的文件
+fn save(path: &Path, stored: &StoredDeviceId) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(stored).map_err(|e| e.to_string())?;
+    let tmp: PathBuf = path.with_extension("tmp");
+    std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// 判断是否是已有安装，并写入安装标记
+///
+/// 标记不存在时是新安装或引入标记之前的旧版本，旧版本的前端会把日志写入日志目录，
+/// 以其中是否已有日志文件区分
+fn check_existing_install(data_dir: &Path) -> AppResult<bool> {
+    let marker = data_dir.join(INSTALL_MARKER_FILE);
+    if marker.is_file() {
+        return Ok(true);
+    }
+    let existing_install = std::fs::read_dir(data_dir.join(LOG_DIR_NAME))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .any(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+        })
+        .unwrap_or(false);
+    std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    std::fs::write(&marker, now_millis().to_string()).map_err(|e| e.to_string())?;
+    Ok(existing_install)
+}
+
+/// 加载或生成设备 id
 ///
-/// # 工作原理
-/// 1. 获取第一个网络接口的 MAC 地址
-/// 2. 获取系统主机名
-/// 3. 使用 UUID v5 算法基于 MAC 地址和主机名生成设备 ID
+/// 需要在 setup 阶段最先调用：旧版本的判断依赖日志目录中的文件，必须在日志初始化写入日志之前进行。
+/// 此时日志还没有初始化，生成的设备 id 来源由调用方在日志初始化之后记录
+///
+/// # 返回值
+/// - `Ok(Some(source))`: 本次新生成了设备 id（包括替换旧版本的 id）
+/// - `Ok(None)`: 使用已保存的设备 id
+pub fn init(app: &tauri::AppHandle) -> AppResult<Option<DeviceIdSource>> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let path = data_dir.join(DEVICE_ID_FILE);
+    let existing_install = check_existing_install(&data_dir)?;
+    let mut generated = None;
+
+    let stored = match load(&path) {
+        Some(stored) if stored.source == DeviceIdSource::Legacy => {
+            // 已经保存的旧 id 同样替换，设置保持不变
+            let (device_id, source) = new_device_id();
+            let stored = StoredDeviceId {
+                device_id,
+                source,
+                salt: new_salt(),
+                previous_device_id: Some(stored.device_id),
+                ..stored
+            };
+            save(&path, &stored)?;
+            generated = Some(stored.source);
+            stored
+        }
+        Some(mut stored) => {
+            if stored.salt.is_empty() {
+                stored.salt = new_salt();
+                save(&path, &stored)?;
+            }
+            stored
+        }
+        None => {
+            // device_id.json 损坏或被删除时，只要是已有安装同样需要服务端映射旧 id
+            let previous =
+                (existing_install || path.exists()).then(|| Fingerprint::current().legacy_id());
+            let stored = generate(previous);
+            save(&path, &stored)?;
+            generated = Some(stored.source);
+            stored
+        }
+    };
+
+    let _ = STORE.set(DeviceStore {
+        path,
+        stored: Mutex::new(stored),
+    });
+    Ok(generated)
+}
+
+/// 设备隐私设置
+#[derive(Debug, Clone, Serialize)]
+pub struct DevicePrivacy {
+    /// 是否允许把原始 MAC 地址返回给前端
+    pub expose_mac_address: bool,
+    /// 计算设备指纹使用的信号名称
+    pub fingerprint_signals: Vec<String>,
+}
+
+/// 设备指纹使用的信号名称列表
+fn fingerprint_signals() -> Vec<String> {
+    FINGERPRINT_SIGNALS.iter().map(|s| s.to_string()).collect()
+}
+
+/// 读取当前保存的设备 id 信息
+fn stored() -> Option<StoredDeviceId> {
+    STORE
+        .get()
+        .and_then(|store| store.stored.lock().ok().map(|stored| stored.clone()))
+}
+
+/// 获取设备隐私设置
+#[tauri::command]
+pub fn get_device_privacy() -> DevicePrivacy {
+    DevicePrivacy {
+        expose_mac_address: stored().is_some_and(|stored| stored.expose_mac_address),
+        fingerprint_signals: fingerprint_signals(),
+    }
+}
+
+/// 设置是否允许把原始 MAC 地址返回给前端
+///
+/// # 参数
+/// - `enabled`: 用户明确同意后才应设置为 true
+#[tauri::command]
+pub fn set_mac_address_exposure(enabled: bool) -> AppResult<DevicePrivacy> {
+    let store = STORE
+        .get()
+        .ok_or_else(|| "Device id is not initialized".to_string())?;
+    {
+        let mut stored = store.stored.lock().map_err(|e| e.to_string())?;
+        stored.expose_mac_address = enabled;
+        save(&store.path, &stored)?;
+    }
+    tracing::info!(enabled, "MAC 地址上报设置已修改");
+    Ok(get_device_privacy())
+}
+
+/// 获取设备唯一标识信息
+///
+/// 返回持久化的稳定设备 id 和加盐哈希后的设备指纹，
+/// 只有用户开启 MAC 地址上报时才返回原始 MAC 地址
 ///
 /// # 返回值
 /// - `Ok(DeviceInfo)`: 成功获取设备信息
 /// - `Err(String)`: 获取失败，包含错误描述
 #[tauri::command]
 pub fn get_device_info() -> Result<DeviceInfo, String> {
-    // 尝试获取第一个MAC地址
-    // get_mac_address() 返回 Result<Option<MacAddress>, Error>
-    let mac = match get_mac_address() {
-        Ok(Some(ma)) => ma.to_string(), // 成功获取到 MAC 地址，转换为字符串
-        _ => "unknown".to_string(),     // 获取失败或没有 MAC 地址，使用默认值
-    };
+    let fingerprint = Fingerprint::current();
 
-    // 基于MAC地址和主机名生成设备ID
-    // hostname::get() 获取系统主机名
-    let hostname = match hostname::get() {
-        Ok(h) => h.to_string_lossy().to_string(), // 成功获取主机名并转换为 UTF-8 字符串
-        Err(_) => "unknown-host".to_string(),     // 获取失败时使用默认值
-    };
+    // 初始化之前（如应用构建阶段的崩溃报告）或加载失败时，使用本次运行内临时生成的 id 和盐
+    match stored() {
+        Some(stored) => Ok(DeviceInfo {
+            mac_address: stored
+                .expose_mac_address
+                .then(|| fingerprint.mac_address.clone()),
+            device_id: stored.device_id,
+            source: Some(stored.source),
+            previous_device_id: stored.previous_device_id,
+            fingerprint: fingerprint.hash(&stored.salt),
+            fingerprint_signals: fingerprint_signals(),
+        }),
+        None => {
+            let temporary = TEMPORARY.get_or_init(|| generate(None));
+            Ok(DeviceInfo {
+                mac_address: None,
+                device_id: temporary.device_id.clone(),
+                source: None,
+                previous_device_id: None,
+                fingerprint: fingerprint.hash(&temporary.salt),
+                fingerprint_signals: fingerprint_signals(),
+            })
+        }
+    }
+}
 
-    // 创建一个UUID v5，以MAC地址和主机名作为命名空间
-    // UUID v5 使用 SHA-1 哈希算法，基于命名空间和名称生成确定性的 UUID
-    // 这意味着相同的输入总是会生成相同的 UUID
-    let namespace = Uuid::new_v5(&Uuid::NAMESPACE_DNS, b"tauri.device.id");
-    let device_id =
-        Uuid::new_v5(&namespace, format!("{}:{}", hostname, mac).as_bytes()).to_string();
+/// 确认服务端已经把旧设备 id 映射到新 id，删除保存的旧 id
+///
+/// 前端在登录时上报 `previous_device_id` 并得到服务端确认后调用
+#[tauri::command]
+pub fn confirm_device_id_migration() -> AppResult<()> {
+    let store = STORE
+        .get()
+        .ok_or_else(|| "Device id is not initialized".to_string())?;
+    let mut stored = store.stored.lock().map_err(|e| e.to_string())?;
+    if stored.previous_device_id.take().is_some() {
+        save(&store.path, &stored)?;
+        tracing::info!("旧设备 id 映射已确认");
+    }
+    Ok(())
+}
 
-    // 构造并返回设备信息
-    Ok(DeviceInfo {
-        mac_address: mac,
-        device_id,
+/// 网络接口信息
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkInterface {
+    /// 接口名称
+    pub name: String,
+    /// MAC 地址，只有用户开启 MAC 地址上报后才有值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    /// IP 地址，形如 `192.168.1.2/24`
+    pub addresses: Vec<String>,
+}
+
+/// 设备清单
+///
+/// 汇总客服排查问题常用的设备信息，也用于填充 SignIn.app_version 和登录时的设备元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInventory {
+    /// 稳定的设备 id
+    pub device_id: String,
+    /// 应用版本
+    pub app_version: String,
+    /// 操作系统类型，如 Linux、Windows、Macos
+    pub os_type: String,
+    /// 操作系统版本
+    pub os_version: String,
+    /// 平台，如 linux、windows、macos
+    pub platform: String,
+    /// CPU 架构
+    pub arch: String,
+    /// 系统语言，如 zh-CN
+    pub locale: Option<String>,
+    /// 逻辑 CPU 数量
+    pub cpu_count: usize,
+    /// 物理核心数量，部分平台无法获取
+    pub physical_core_count: Option<usize>,
+    /// 内存总量（字节）
+    pub total_memory: u64,
+    /// 可用内存（字节）
+    pub available_memory: u64,
+    /// 显示器数量
+    pub display_count: usize,
+    /// 系统已运行时间（秒）
+    pub uptime: u64,
+    /// 网络接口，不包含回环接口
+    pub network_interfaces: Vec<NetworkInterface>,
+}
+
+/// 列出网络接口
+///
+/// # 参数
+/// - `expose_mac_address`: 是否包含 MAC 地址
+fn network_interfaces(expose_mac_address: bool) -> Vec<NetworkInterface> {
+    let networks = Networks::new_with_refreshed_list();
+    let mut interfaces: Vec<NetworkInterface> = networks
+        .iter()
+        .filter(|(_, data)| !data.ip_networks().iter().any(|ip| ip.addr.is_loopback()))
+        .map(|(name, data)| NetworkInterface {
+            name: name.clone(),
+            mac_address: expose_mac_address.then(|| data.mac_address().to_string()),
+            addresses: data
+                .ip_networks()
+                .iter()
+                .map(|ip| format!("{}/{}", ip.addr, ip.prefix))
+                .collect(),
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+/// 获取设备清单
+///
+/// 收集系统信息需要读取较多系统文件，放到阻塞线程池中执行
+#[tauri::command]
+pub async fn get_device_inventory(app: tauri::AppHandle) -> AppResult<DeviceInventory> {
+    let device_id = get_device_info()?.device_id;
+    let expose_mac_address = stored().is_some_and(|stored| stored.expose_mac_address);
+    let display_count = app
+        .available_monitors()
+        .map(|monitors| monitors.len())
+        .unwrap_or(0);
+    let app_version = app.package_info().version.to_string();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let system = System::new_with_specifics(
+            RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+        );
+        DeviceInventory {
+            device_id,
+            app_version,
+            os_type: tauri_plugin_os::type_().to_string(),
+            os_version: tauri_plugin_os::version().to_string(),
+            platform: tauri_plugin_os::platform().to_string(),
+            arch: tauri_plugin_os::arch().to_string(),
+            locale: tauri_plugin_os::locale(),
+            cpu_count: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            physical_core_count: system.physical_core_count(),
+            total_memory: system.total_memory(),
+            available_memory: system.available_memory(),
+            display_count,
+            uptime: System::uptime(),
+            network_interfaces: network_interfaces(expose_mac_address),
+        }
     })
+    .await
+    .map_err(|e| e.to_string())
 }
@@ -0,0 +1,252 @@
+/// 磁盘内容缓存模块
+///
+/// 以 SHA-256 哈希作为文件名，把下载的图片、音视频等二进制数据保存在应用缓存目录中
+/// 键可以是内容本身的哈希（内容寻址，相同内容只保存一份），也可以是远程地址的哈希
+/// 整个缓存目录有总大小上限，每次读取都会刷新文件的修改时间，
+/// 超出上限时按最后修改时间从旧到新淘汰文件，即最近最少使用（LRU）淘汰
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use crate::utils::AppResult;
+
+/// 带大小上限的磁盘缓存
+#[derive(Debug)]
+pub struct DiskCache {
+    /// 缓存文件所在目录
+    dir: PathBuf,
+    /// 缓存目录允许占用的最大字节数
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    /// 创建磁盘缓存，目录不存在时自动创建
+    ///
+    /// 在启动时创建，此时还没有写入中的临时文件，上次运行中断（崩溃、退出时取消下载）
+    /// 留下的临时文件在这里删除
+    ///
+    /// # 参数
+    /// - `dir`: 缓存目录
+    /// - `max_bytes`: 缓存总大小上限（字节）
+    pub fn new(dir: PathBuf, max_bytes: u64) -> AppResult<Self> {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        remove_temp_files(&dir);
+        Ok(Self { dir, max_bytes })
+    }
+
+    /// 计算内容的哈希值（小写十六进制）
+    pub fn hash(bytes: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(bytes))
+    }
+
+    /// 获取键对应的缓存文件路径
+    ///
+    /// 只接受 64 位十六进制字符串，防止通过自定义协议传入 `../` 之类的路径读取缓存目录之外的文件
+    pub fn path_for(&self, key: &str) -> Option<PathBuf> {
+        let valid = key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit());
+        valid.then(|| self.dir.join(key.to_ascii_lowercase()))
+    }
+
+    /// 判断缓存中是否存在指定键的内容
+    pub fn contains(&self, key: &str) -> bool {
+        self.path_for(key).is_some_and(|path| path.is_file())
+    }
+
+    /// 以内容哈希为键写入内容，并返回该哈希
+    ///
+    /// 内容已存在时不会重复写入
+    pub fn store(&self, bytes: &[u8]) -> AppResult<String> {
+        let hash = Self::hash(bytes);
+        if !self.contains(&hash) {
+            self.store_as(&hash, bytes)?;
+        }
+        Ok(hash)
+    }
+
+    /// 以指定的键写入内容，写入后会检查并执行大小上限淘汰
+    pub fn store_as(&self, key: &str, bytes: &[u8]) -> AppResult<()> {
+        // 先写临时文件再重命名，避免读取到写了一半的文件
+        let tmp = self.temp_path(key)?;
+        fs::write(&tmp, bytes).map_err(|e| e.to_string())?;
+        self.commit(key)
+    }
+
+    /// 获取键对应的临时文件路径
+    ///
+    /// 较大的文件（如视频）可以先流式写入临时文件，再调用 `commit` 放入缓存
+    pub fn temp_path(&self, key: &str) -> AppResult<PathBuf> {
+        self.path_for(key)
+            .map(|path| path.with_extension("tmp"))
+            .ok_or_else(|| format!("Invalid cache key: {}", key))
+    }
+
+    /// 把临时文件移动到正式位置，并执行大小上限淘汰
+    pub fn commit(&self, key: &str) -> AppResult<()> {
+        let path = self
+            .path_for(key)
+            .ok_or_else(|| format!("Invalid cache key: {}", key))?;
+        fs::rename(self.temp_path(key)?, &path).map_err(|e| e.to_string())?;
+        self.enforce_limit()
+    }
+
+    /// 读取指定键的全部内容
+    pub fn read(&self, key: &str) -> AppResult<Vec<u8>> {
+        let path = self.existing_path(key)?;
+        touch(&path);
+        fs::read(path).map_err(|e| e.to_string())
+    }
+
+    /// 读取指定键的一段内容
+    ///
+    /// # 参数
+    /// - `start`: 起始偏移（包含）
+    /// - `end`: 结束偏移（包含）
+    pub fn read_range(&self, key: &str, start: u64, end: u64) -> AppResult<Vec<u8>> {
+        let path = self.existing_path(key)?;
+        touch(&path);
+        read_file_range(&path, start, end)
+    }
+
+    /// 获取指定键的内容长度
+    pub fn len(&self, key: &str) -> AppResult<u64> {
+        let path = self.existing_path(key)?;
+        fs::metadata(path)
+            .map(|meta| meta.len())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 缓存目录当前占用的字节数
+    pub fn size(&self) -> AppResult<u64> {
+        Ok(self.entries()?.iter().map(|entry| entry.1).sum())
+    }
+
+    /// 清空缓存目录中的所有缓存文件
+    pub fn clear(&self) -> AppResult<()> {
+        for (path, _, _) in self.entries()? {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    /// 超出大小上限时按修改时间从旧到新删除文件
+    pub fn enforce_limit(&self) -> AppResult<()> {
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|entry| entry.1).sum();
+        if total <= self.max_bytes {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|entry| entry.2);
+        for (path, len, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(len);
+            }
+        }
+        Ok(())
+    }
+
+    /// 列出缓存文件：(路径, 大小, 修改时间)
+    ///
+    /// 只统计以哈希命名的文件，正在写入的临时文件和索引等辅助文件不参与淘汰
+    fn entries(&self) -> AppResult<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(|e| e.to_string())? {
+            let Ok(entry) = entry else { continue };
+            let Ok(meta) = entry.metadata() else { continue };
+            let is_cache_file = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| self.path_for(name))
+                .is_some();
+            if !meta.is_file() || !is_cache_file {
+                continue;
+            }
+            let modified = meta.modified().unwrap_or(std::time::UNIX_EPOCH);
+            entries.push((entry.path(), meta.len(), modified));
+        }
+        Ok(entries)
+    }
+
+    /// 缓存目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 获取已存在的缓存文件路径
+    fn existing_path(&self, key: &str) -> AppResult<PathBuf> {
+        self.path_for(key)
+            .filter(|path| path.is_file())
+            .ok_or_else(|| format!("Cache miss: {}", key))
+    }
+}
+
+/// 读取文件的一段内容，文件长度不足时只返回已有的部分
+///
+/// # 参数
+/// - `start`: 起始偏移（包含）
+/// - `end`: 结束偏移（包含）
+pub fn read_file_range(path: &Path, start: u64, end: u64) -> AppResult<Vec<u8>> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| e.to_string())?;
+    let mut buf = Vec::new();
+    file.take(end.saturating_sub(start) + 1)
+        .read_to_end(&mut buf)
+        .map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+/// 删除目录中遗留的临时文件（`<键>.tmp`），删除失败时忽略
+fn remove_temp_files(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "tmp") {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// 刷新文件的修改时间，作为 LRU 淘汰的“最近使用时间”
+///
+/// 刷新失败只会影响淘汰顺序，所以忽略错误
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// 根据文件头部的魔数猜测图片的 MIME 类型
+///
+/// 缓存文件以哈希命名、没有扩展名，所以返回给 WebView 时需要通过内容判断类型
+pub fn guess_mime(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'B', b'M', ..] => "image/bmp",
+        _ if bytes.starts_with(b"<svg") || bytes.starts_with(b"<?xml") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 生成自定义协议的访问地址
+///
+/// Tauri 在 Windows 和 Android 上使用 `http://<scheme>.localhost/` 形式，
+/// 其他平台使用 `<scheme>://localhost/` 形式
+pub fn protocol_url(scheme: &str, path: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", scheme, path)
+    } else {
+        format!("{}://localhost/{}", scheme, path)
+    }
+}
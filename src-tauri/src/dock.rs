@@ -76,7 +76,7 @@ pub fn setup_dock_event_handler(_app: &tauri::App) -> Result<(), Box<dyn std::er
     // Tauri v2 中 Dock 事件处理已经在 lib.rs 的 RunEvent::Reopen 中实现
     // 这里保留接口以备将来扩展使用
     // 例如：可以在这里添加其他 macOS 特定的 Dock 设置
-    println!("macOS Dock 事件处理器已在运行时事件中配置");
+    tracing::debug!("macOS Dock 事件处理器已在运行时事件中配置");
     Ok(())
 }
 
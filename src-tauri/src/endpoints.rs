@@ -0,0 +1,86 @@
+/// 服务端接口地址配置模块
+///
+/// 日志上传结果回报、崩溃报告提交等由应用主动请求的服务端接口，地址只来自配置，不接受前端传入：
+/// - 编译时可以通过同名的大写环境变量（如 `LOG_UPLOAD_REPORT_URL`）指定默认地址
+/// - 应用配置目录中的 endpoints.json 可以覆盖编译时的地址，便于私有化部署时修改
+///
+/// 配置只在启动时读取一次，地址必须是 http 或 https
+use serde::Deserialize;
+use tauri::Manager;
+
+/// 配置文件名，位于应用配置目录下
+const ENDPOINTS_FILE: &str = "endpoints.json";
+
+/// 服务端接口地址，未配置的接口为 None
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    /// 日志上传结果回报地址
+    pub log_upload_report: Option<String>,
+    /// 崩溃报告提交地址
+    pub crash_report: Option<String>,
+}
+
+impl Endpoints {
+    /// 编译时配置的地址
+    fn built_in() -> Self {
+        Self {
+            log_upload_report: option_env!("LOG_UPLOAD_REPORT_URL").map(String::from),
+            crash_report: option_env!("CRASH_REPORT_URL").map(String::from),
+        }
+    }
+
+    /// 加载接口地址配置，配置文件中的地址覆盖编译时的地址
+    ///
+    /// 配置文件不存在或格式错误时使用编译时的地址
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        let mut endpoints = Self::built_in();
+        let Ok(path) = app
+            .path()
+            .app_config_dir()
+            .map(|dir| dir.join(ENDPOINTS_FILE))
+        else {
+            return endpoints;
+        };
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Endpoints>(&json) {
+                Ok(file) => endpoints.merge(file),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "接口地址配置格式错误")
+                }
+            }
+        }
+        endpoints.validate();
+        tracing::info!(endpoints = ?endpoints, "接口地址配置已加载");
+        endpoints
+    }
+
+    /// 用另一份配置中已配置的地址覆盖当前地址
+    fn merge(&mut self, other: Endpoints) {
+        if other.log_upload_report.is_some() {
+            self.log_upload_report = other.log_upload_report;
+        }
+        if other.crash_report.is_some() {
+            self.crash_report = other.crash_report;
+        }
+    }
+
+    /// 丢弃不是 http 或 https 的地址
+    fn validate(&mut self) {
+        discard_invalid(&mut self.log_upload_report);
+        discard_invalid(&mut self.crash_report);
+    }
+}
+
+/// 地址不是 http 或 https 时记录日志并丢弃
+fn discard_invalid(url: &mut Option<String>) {
+    if url.as_deref().is_some_and(|u| !is_http_url(u)) {
+        tracing::warn!(url = ?url, "忽略无效的接口地址");
+        *url = None;
+    }
+}
+
+/// 地址是否是 http 或 https
+pub fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
@@ -0,0 +1,130 @@
+/// 应用错误类型模块
+///
+/// 命令返回的错误会序列化为 `{code, message, details}` 交给前端：
+/// - `code`: 稳定的机器可读错误码，前端据此区分错误类型，不会随错误文案变化
+/// - `message`: 可读的错误描述
+/// - `details`: 附加信息（如底层错误、窗口标签），没有时为 null
+///
+/// 尚未迁移的模块仍使用 `utils::AppResult`（String 错误），
+/// `AppError` 可以通过 `?` 自动转换为 String，两者可以混用
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// 应用错误
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// 互斥锁被毒化，持有锁的线程发生过 panic
+    #[error("State lock poisoned: {0}")]
+    LockPoisoned(String),
+
+    /// base64 解码失败
+    #[error("Base64 decode error: {0}")]
+    Base64Decode(#[from] base64::DecodeError),
+
+    /// protobuf 解码失败
+    #[error("Protobuf decode error: {0}")]
+    ProtobufDecode(#[from] prost::DecodeError),
+
+    /// 托盘图标不存在
+    #[error("Tray icon not found: {0}")]
+    TrayNotFound(String),
+
+    /// 窗口不存在
+    #[error("Window not found: {0}")]
+    WindowNotFound(String),
+
+    /// Tauri 内部错误
+    #[error("Tauri error: {0}")]
+    Tauri(#[from] tauri::Error),
+
+    /// 设置无效，如格式错误、时间或快捷键无法解析
+    #[error("Invalid settings: {0}")]
+    InvalidSettings(String),
+
+    /// 深度链接无效，如 scheme 不支持、id 不是正整数
+    #[error("Invalid deep link: {0}")]
+    InvalidDeepLink(String),
+
+    /// 链接不允许打开，如 file: 等非 http/https 链接
+    #[error("Blocked link: {0}")]
+    BlockedLink(String),
+
+    /// 修改系统的开机自启失败
+    #[error("Autostart error: {0}")]
+    Autostart(String),
+
+    /// 文件读写错误
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// 其他错误，来自尚未迁移到 AppError 的模块
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::LockPoisoned(_) => "LOCK_POISONED",
+            Self::Base64Decode(_) => "BASE64_DECODE",
+            Self::ProtobufDecode(_) => "PROTOBUF_DECODE",
+            Self::TrayNotFound(_) => "TRAY_NOT_FOUND",
+            Self::WindowNotFound(_) => "WINDOW_NOT_FOUND",
+            Self::Tauri(_) => "TAURI",
+            Self::InvalidSettings(_) => "INVALID_SETTINGS",
+            Self::InvalidDeepLink(_) => "INVALID_DEEP_LINK",
+            Self::BlockedLink(_) => "BLOCKED_LINK",
+            Self::Autostart(_) => "AUTOSTART",
+            Self::Io(_) => "IO",
+            Self::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// 附加信息
+    pub fn details(&self) -> Option<String> {
+        match self {
+            Self::LockPoisoned(details)
+            | Self::TrayNotFound(details)
+            | Self::WindowNotFound(details)
+            | Self::InvalidSettings(details)
+            | Self::InvalidDeepLink(details)
+            | Self::BlockedLink(details)
+            | Self::Autostart(details) => Some(details.clone()),
+            Self::Base64Decode(e) => Some(format!("{:?}", e)),
+            Self::ProtobufDecode(e) => Some(format!("{:?}", e)),
+            Self::Tauri(e) => Some(format!("{:?}", e)),
+            Self::Io(e) => Some(format!("{:?}", e.kind())),
+            Self::Internal(_) => None,
+        }
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::LockPoisoned(e.to_string())
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+/// 兼容仍使用 String 错误的模块
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
@@ -2,18 +2,36 @@
 // 在 Rust 中，模块系统用于组织代码
 // mod 关键字声明一个模块，这里声明的模块对应同名的 .rs 文件
 mod app_config; // 应用程序配置和插件管理
+mod apps_upload; // 业务应用安装与运行状态上报
+mod chat_windows; // 聊天弹出窗口
 mod commands; // Tauri 命令处理函数
+mod crash; // panic 崩溃报告
+mod deep_link; // 启动参数中的深度链接
 mod device_id; // 设备标识信息获取
+mod disk_cache; // 内容寻址的磁盘缓存
 mod dock; // macOS Dock 徽章管理
+mod endpoints; // 服务端接口地址配置
+mod error; // 命令错误类型与错误码
+mod lifecycle; // 关闭行为与退出流程
+mod links; // 消息链接路由
+mod log_upload; // 日志收集与上传
+mod logging; // tracing 日志与按天滚动的日志文件
+mod media_cache; // 聊天媒体资源缓存与 cache:// 协议
+mod notices; // 系统公告与公告弹窗
+mod notifications; // 新消息系统通知
 mod pb; // Protobuf 消息处理
+mod profiles; // 用户资料与头像缓存
+mod reminders; // 记事本提醒调度
+mod room_members; // 群成员与禁言状态缓存
+mod settings; // 应用设置
+mod shortcuts; // 全局快捷键
+mod shutdown; // 后台任务停止协调
 mod tray; // 系统托盘管理
 mod unread_count; // 未读消息数量状态管理
 pub mod utils; // 通用工具函数
+mod voice_broadcast; // 语音播报队列与 TTS 后端
 mod window; // 窗口管理功能
 
-// 导入必要的 trait
-use tauri::Manager;
-
 // 重新导出主要模块
 // pub use 将模块中的类型重新导出，使其可以在库的根级别访问
 // 这样外部代码就可以直接使用 demo_lib::UnreadCount 而不是 demo_lib::unread_count::UnreadCount
@@ -33,10 +51,40 @@ pub use pb::*; // 导出所有 protobuf 类型
 /// 5. 处理平台特定的功能（如 macOS 的 Dock 事件）
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 最先安装 panic 钩子，构建应用失败等 panic 也会留下崩溃报告
+    crash::install_panic_hook();
+
     // 创建默认的 UnreadCount 实例
     // 这个实例将作为全局状态在整个应用程序中共享
     let unread_count = UnreadCount::new();
 
+    // 群成员缓存，由群成员相关的推送消息驱动更新
+    let room_members = room_members::RoomMembers::new();
+
+    // 新消息通知状态（免打扰会话、每个会话的通知计数）
+    let notifications = notifications::Notifications::new();
+
+    // 窗口注册表，记录通过 window 模块打开的窗口
+    let window_registry = window::WindowRegistry::new();
+
+    // 后台任务停止协调器，各子系统启动后台任务时登记，退出时统一停止
+    let shutdown = shutdown::Shutdown::new();
+
+    // 退出流程状态，等待前端完成退出前的工作
+    let lifecycle = lifecycle::Lifecycle::new();
+
+    // 全局快捷键注册结果，快捷键在 setup 中加载设置后注册
+    let global_shortcuts = shortcuts::GlobalShortcuts::new();
+
+    // 深度链接状态，保存主窗口前端就绪前收到的链接
+    let deep_links = deep_link::DeepLinks::new();
+
+    // 托盘状态，记录提醒高亮内容，未读数变化时不覆盖
+    let tray_state = tray::TrayState::new();
+
+    // 等待前端上传的日志任务
+    let log_uploads = log_upload::LogUploads::new();
+
     // 使用 Builder 模式创建并配置 Tauri 应用
     let builder = tauri::Builder::default();
 
@@ -48,26 +96,134 @@ pub fn run() {
         // 使用 manage 将状态添加到应用程序中，使其可在各命令间共享
         // 这样所有的命令函数都可以通过 State<UnreadCount> 参数访问这个状态
         .manage(unread_count)
+        .manage(room_members)
+        .manage(notifications)
+        .manage(window_registry)
+        .manage(shutdown)
+        .manage(lifecycle)
+        .manage(global_shortcuts)
+        .manage(deep_links)
+        .manage(tray_state)
+        .manage(log_uploads)
         // 设置应用程序初始化函数，在应用启动时调用
         .setup(app_config::setup_app)
+        // 注册头像自定义协议，WebView 通过 avatar://localhost/<hash> 读取本地缓存的头像
+        .register_asynchronous_uri_scheme_protocol(
+            profiles::AVATAR_SCHEME,
+            profiles::handle_avatar_protocol,
+        )
+        // 注册媒体缓存自定义协议，WebView 通过 cache://localhost/<编码后的地址> 读取远程媒体
+        .register_asynchronous_uri_scheme_protocol(
+            media_cache::MEDIA_SCHEME,
+            media_cache::handle_media_protocol,
+        )
         // 设置系统托盘图标事件处理器
         .on_tray_icon_event(app_config::handle_tray_event)
         // 设置托盘菜单事件处理器
         .on_menu_event(app_config::handle_menu_event)
+        // 设置页面加载事件处理器，主窗口重新加载时重置深度链接的就绪状态
+        .on_page_load(deep_link::handle_page_load)
         // 注册可以从前端调用的命令处理函数
         // generate_handler! 宏会生成必要的代码来路由前端调用到这些函数
         .invoke_handler(tauri::generate_handler![
             // 注册可以从前端调用的命令处理函数
-            commands::greet,            // 处理问候功能
-            commands::increment_unread, // 增加未读消息数
-            commands::get_unread_count, // 获取当前未读消息数
-            commands::clear_unread,     // 清除未读消息数
-            device_id::get_device_info, // 获取设备信息
+            commands::greet,                        // 处理问候功能
+            commands::increment_unread,             // 增加未读消息数
+            commands::get_unread_count,             // 获取当前未读消息数
+            commands::clear_unread,                 // 清除未读消息数
+            device_id::get_device_info,             // 获取设备信息
+            device_id::confirm_device_id_migration, // 确认旧设备 id 映射完成
+            device_id::get_device_inventory,        // 获取设备清单
+            device_id::get_device_privacy,          // 获取设备隐私设置
+            device_id::set_mac_address_exposure,    // 设置是否上报 MAC 地址
+            // 窗口管理相关命令
+            window::open_app_window,     // 打开或聚焦固定窗口
+            window::show_app_window,     // 显示窗口
+            window::hide_app_window,     // 隐藏窗口
+            window::toggle_app_window,   // 切换窗口显示状态
+            window::list_open_windows,   // 获取已打开的窗口
+            window::reset_window_layout, // 重置窗口布局
+            // 关闭行为与退出相关命令
+            lifecycle::get_close_settings, // 获取关闭行为设置
+            lifecycle::set_close_behavior, // 设置关闭按钮的行为
+            lifecycle::quit_app,           // 退出应用
+            lifecycle::confirm_quit,       // 前端完成退出前的工作
+            // 设置相关命令
+            settings::get_settings,         // 获取设置
+            settings::patch_settings,       // 修改设置
+            shortcuts::get_shortcut_status, // 获取全局快捷键注册结果
+            // 深度链接相关命令
+            deep_link::take_pending_deep_links, // 取走前端就绪前收到的深度链接
+            // 消息链接相关命令
+            links::classify_link,  // 获取链接类型
+            links::open_link,      // 按类型打开链接
+            links::open_app_link,  // 打开应用链接消息中的链接
+            links::open_task_link, // 打开任务消息的按钮链接
+            // 聊天弹出窗口相关命令
+            chat_windows::open_chat_window,       // 打开会话的弹出窗口
+            chat_windows::list_chat_windows,      // 获取打开的弹出窗口
+            chat_windows::get_chat_window_unread, // 获取弹出窗口的未读数
+            chat_windows::mark_chat_window_read,  // 清零弹出窗口的未读数
             // Protobuf 相关命令
             commands::create_event_message,  // 创建事件消息
             commands::parse_event_message,   // 解析事件消息
             commands::create_message_send,   // 创建发送消息
             commands::parse_message_send,    // 解析发送消息
+            commands::apply_event_common,    // 处理公共事件
+            // 群成员相关命令
+            room_members::apply_member_update,          // 应用成员资料更新
+            room_members::apply_member_change,          // 应用进群/退群消息
+            room_members::apply_member_level_change,    // 应用成员权限变更
+            room_members::apply_member_ban,             // 应用禁言/解除禁言
+            room_members::apply_chatroom_member_delete, // 应用移除成员消息
+            room_members::get_room_members,             // 获取群成员列表
+            room_members::is_muted_in_room,             // 查询是否被禁言
+            // 用户资料相关命令
+            profiles::apply_user_info_update,  // 应用用户资料更新事件
+            profiles::apply_user_profile,      // 应用 User 消息
+            profiles::get_user_profile,        // 获取用户资料
+            profiles::invalidate_user_profile, // 使用户资料缓存失效
+            // 媒体缓存相关命令
+            media_cache::get_cached_media_url, // 获取远程资源的本地缓存地址
+            media_cache::get_media_cache_size, // 获取媒体缓存占用大小
+            media_cache::clear_media_cache,    // 清空媒体缓存
+            // 消息通知相关命令
+            notifications::apply_message_push,               // 处理消息投递并弹出通知
+            notifications::set_conversation_muted,           // 设置会话免打扰
+            notifications::clear_conversation_notifications, // 清除会话通知计数
+            // 系统公告相关命令
+            notices::apply_notice,          // 应用系统公告推送
+            notices::apply_announce_notice, // 应用宣发公告推送
+            notices::list_notices,          // 获取公告列表
+            notices::acknowledge_notice,    // 确认公告弹窗
+            // 记事本提醒相关命令
+            reminders::apply_remind_note_push, // 应用记事本提醒推送
+            reminders::list_reminders,         // 获取提醒列表
+            reminders::snooze_reminder,        // 稍后提醒
+            reminders::dismiss_reminder,       // 关闭提醒
+            // 语音播报相关命令
+            voice_broadcast::get_voice_broadcast_settings, // 获取播报设置
+            voice_broadcast::set_voice_broadcast_volume,   // 设置播报音量
+            voice_broadcast::set_voice_broadcast_muted,    // 设置播报静音
+            // 日志相关命令
+            log_upload::prepare_device_log_upload, // 收集并压缩服务端要求上传的日志
+            log_upload::finish_device_log_upload,  // 回报日志上传结果
+            logging::log_write,                    // 写入前端日志
+            logging::get_log_filter,               // 获取日志过滤规则
+            logging::set_log_filter,               // 替换日志过滤规则
+            logging::set_log_level,                // 设置模块日志级别
+            logging::read_log_lines,               // 分页读取日志文件
+            logging::list_log_files,               // 获取指定日期的日志文件
+            logging::set_log_streaming,            // 开启或关闭日志实时推送
+            // 应用状态上报相关命令
+            apps_upload::apply_apps_upload,  // 生成应用状态上报消息
+            apps_upload::get_monitored_apps, // 获取需要检查的应用
+            apps_upload::set_monitored_apps, // 设置需要检查的应用
+            // 崩溃报告相关命令
+            crash::set_connection_state,  // 上报连接状态
+            crash::list_crash_reports,    // 获取未提交的崩溃报告
+            crash::submit_crash_reports,  // 提交崩溃报告
+            crash::discard_crash_reports, // 丢弃崩溃报告
         ])
         // 构建应用程序
         .build(tauri::generate_context!())
@@ -84,23 +240,29 @@ pub fn run() {
             tauri::RunEvent::Reopen { .. } => {
                 // 修复：无论是否有其他可见窗口，只要主窗口隐藏就显示它
                 // 这样解决了当存在多个窗口时，主窗口隐藏后无法通过 Dock 图标恢复的问题
-                if let Some(main_window) = app_handle.get_webview_window("main") {
+                if let Some(main_window) = crate::window::main_window(app_handle) {
                     // 检查主窗口是否隐藏
                     // is_visible() 返回 Result<bool, Error>，如果出错则假设窗口不可见
                     if !main_window.is_visible().unwrap_or(false) {
-                        println!("Dock 图标被点击，恢复隐藏的主窗口");
+                        tracing::info!("Dock 图标被点击，恢复隐藏的主窗口");
                         crate::window::show_main_window(app_handle);
                     } else {
                         // 如果主窗口已经可见，则将其置于前台
-                        println!("Dock 图标被点击，主窗口已可见，将其置于前台");
+                        tracing::info!("Dock 图标被点击，主窗口已可见，将其置于前台");
                         let _ = main_window.set_focus();
                     }
                 } else {
                     // 如果主窗口不存在（这种情况很少见），尝试显示它
-                    println!("Dock 图标被点击，但主窗口不存在，尝试显示");
+                    tracing::warn!("Dock 图标被点击，但主窗口不存在，尝试显示");
                     crate::window::show_main_window(app_handle);
                 }
             }
+            // 系统发起的退出（如 macOS 的 Cmd+Q）也走完整的退出流程，
+            // 退出流程自己调用 app.exit 时放行
+            tauri::RunEvent::ExitRequested { api, .. } if !lifecycle::is_quitting() => {
+                api.prevent_exit();
+                lifecycle::quit(app_handle);
+            }
             _ => {}
         }
     });
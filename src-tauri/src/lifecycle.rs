@@ -0,0 +1,201 @@
+/// 应用关闭行为与退出流程模块
+///
+/// 主窗口的关闭按钮按设置中的 `close_behavior` 处理：
+/// - `hide`: 隐藏到系统托盘（默认），第一次关闭时弹窗说明托盘的用法
+/// - `minimize`: 最小化窗口
+/// - `quit`: 退出应用
+///
+/// 关闭行为保存在应用设置中（见 settings 模块），`get_close_settings` 和 `set_close_behavior`
+/// 只读写其中与关闭行为相关的字段
+///
+/// 所有退出入口（托盘菜单的“退出”、关闭行为为 quit、系统发起的退出如 macOS 的 Cmd+Q）
+/// 都经过 `quit`，按顺序完成：
+/// 1. 向前端发送 "app-will-quit" 事件，等待前端完成收尾工作（如写完日志）后调用 `confirm_quit`，
+///    最多等待 `QUIT_TIMEOUT`
+/// 2. 通知后台任务停止并等待，见 shutdown 模块
+/// 3. 保存窗口状态
+/// 4. 写入退出日志并刷新输出
+/// 5. 调用 `app.exit`，store 插件在退出事件中保存前端的 store 文件
+///
+/// Rust 侧的存储（设置、公告、提醒、弹出窗口等）每次修改时都已写入磁盘，退出时不需要额外处理
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tokio::sync::Notify;
+
+use crate::error::AppError;
+use crate::settings::{self, CloseBehavior};
+use crate::shutdown::Shutdown;
+use crate::window;
+
+/// 等待前端完成退出前工作的最长时间
+const QUIT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 是否已经开始退出流程，防止重复退出，也用于放行退出流程自己发起的退出请求
+static QUITTING: AtomicBool = AtomicBool::new(false);
+
+/// 应用生命周期状态
+#[derive(Default)]
+pub struct Lifecycle {
+    /// 前端完成退出前工作的通知
+    quit_ready: Notify,
+}
+
+impl Lifecycle {
+    /// 创建生命周期状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// 关闭行为设置，是应用设置中 `close_behavior` 和 `tray_hint_shown` 的视图
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CloseSettings {
+    /// 关闭按钮的行为
+    pub behavior: CloseBehavior,
+    /// 是否已经显示过托盘说明
+    pub tray_hint_shown: bool,
+}
+
+impl From<&settings::Settings> for CloseSettings {
+    fn from(settings: &settings::Settings) -> Self {
+        Self {
+            behavior: settings.close_behavior,
+            tray_hint_shown: settings.tray_hint_shown,
+        }
+    }
+}
+
+/// 是否已经开始退出流程
+pub fn is_quitting() -> bool {
+    QUITTING.load(Ordering::SeqCst)
+}
+
+/// 处理主窗口的关闭请求
+///
+/// 在主窗口的 CloseRequested 事件中调用，调用前已经阻止了窗口关闭
+pub fn handle_close_requested(app: &tauri::AppHandle) {
+    let settings = settings::current(app);
+
+    match settings.close_behavior {
+        CloseBehavior::Hide if !settings.tray_hint_shown => show_tray_hint(app),
+        CloseBehavior::Hide => window::hide_main_window(app),
+        CloseBehavior::Minimize => {
+            if let Some(main) = window::main_window(app) {
+                let _ = main.minimize();
+            }
+        }
+        CloseBehavior::Quit => quit(app),
+    }
+}
+
+/// 第一次关闭主窗口时说明托盘的用法，用户也可以在这里改为关闭时退出
+///
+/// 对话框的回调只区分确认和取消，按 Esc 或关闭对话框同样算作取消，
+/// 所以“退出应用”放在确认按钮上，取消和直接关闭对话框都按继续在后台运行处理
+fn show_tray_hint(app: &tauri::AppHandle) {
+    let app_handle = app.clone();
+    app.dialog()
+        .message("关闭窗口后应用会继续在系统托盘中运行，可以点击托盘图标重新打开窗口，或在托盘菜单中选择“退出”结束应用。\n\n之后可以在设置中修改关闭按钮的行为。")
+        .title("应用将在后台运行")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "退出应用".to_string(),
+            "最小化到托盘".to_string(),
+        ))
+        .show(move |quit_app| {
+            let keep_running = !quit_app;
+            let result = settings::update(&app_handle, |settings| {
+                settings.tray_hint_shown = true;
+                if !keep_running {
+                    settings.close_behavior = CloseBehavior::Quit;
+                }
+            });
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "保存关闭行为设置失败");
+            }
+
+            if keep_running {
+                window::hide_main_window(&app_handle);
+            } else {
+                quit(&app_handle);
+            }
+        });
+}
+
+/// 退出应用
+///
+/// 通知前端完成退出前的工作，保存窗口状态和日志后退出；重复调用时忽略
+pub fn quit(app: &tauri::AppHandle) {
+    if QUITTING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tracing::info!("开始退出应用");
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // 1. 等待前端完成收尾工作
+        if let Some(state) = app.try_state::<Lifecycle>() {
+            let ready = state.quit_ready.notified();
+            if app.emit("app-will-quit", ()).is_ok()
+                && tokio::time::timeout(QUIT_TIMEOUT, ready).await.is_err()
+            {
+                tracing::warn!("等待前端完成退出前工作超时");
+            }
+        }
+
+        // 2. 停止后台任务
+        if let Some(shutdown) = app.try_state::<Shutdown>() {
+            shutdown.shutdown().await;
+        }
+
+        // 3. 保存窗口状态
+        window::save_window_state(&app);
+
+        // 4. 写入退出日志，文件日志直接写入文件，只需要刷新控制台输出
+        tracing::info!("应用退出");
+        let _ = std::io::stdout().flush();
+
+        // 5. 退出，store 插件会在退出事件中保存
+        app.exit(0);
+    });
+}
+
+/// 获取关闭行为设置
+#[tauri::command]
+pub fn get_close_settings(app: tauri::AppHandle) -> CloseSettings {
+    CloseSettings::from(&settings::current(&app))
+}
+
+/// 设置主窗口关闭按钮的行为，保存在应用设置中
+///
+/// # 参数
+/// - `behavior`: hide、minimize 或 quit
+#[tauri::command]
+pub fn set_close_behavior(
+    app: tauri::AppHandle,
+    behavior: CloseBehavior,
+) -> Result<CloseSettings, AppError> {
+    let settings = settings::update(&app, |settings| {
+        settings.close_behavior = behavior;
+        // 主动选择过关闭行为，不需要再显示托盘说明
+        settings.tray_hint_shown = true;
+    })?;
+    Ok(CloseSettings::from(&settings))
+}
+
+/// 退出应用，与托盘菜单的“退出”相同
+#[tauri::command]
+pub fn quit_app(app: tauri::AppHandle) {
+    quit(&app);
+}
+
+/// 前端完成退出前的工作（如写完日志）后调用，应用随后退出
+#[tauri::command]
+pub fn confirm_quit(state: State<Lifecycle>) {
+    state.quit_ready.notify_one();
+}
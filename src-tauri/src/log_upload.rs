@@ -0,0 +1,572 @@
+/// 日志上传模块
+///
+/// 客服通过 EventDeviceLogUpload（log_date、log_url）要求设备上传指定日期的日志：
+/// 1. `prepare_device_log_upload`：收集应用日志目录中该日期的日志文件（`YYYY-MM-DD.<类型>.log`）
+///    并压缩为一个 zip 文件
+/// 2. 前端通过 tauri-plugin-upload 的 upload 把 zip 文件上传到 log_url
+/// 3. `finish_device_log_upload`：删除 zip 文件，并把上传结果回报到配置的回报地址（见 endpoints 模块）
+///
+/// 收集失败时第 1 步直接回报失败。日志文件由 tracing-appender 按 UTC 日期命名，
+/// log_date 同样按 UTC 日期理解
+///
+/// 测试中使用本地 HTTP 桩服务作为 log_url 和回报地址，覆盖收集、压缩、上传和回报的完整流程
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{Manager, State};
+use tauri_plugin_http::reqwest;
+use zip::write::SimpleFileOptions;
+
+use crate::device_id;
+use crate::endpoints::{self, Endpoints};
+use crate::pb::*;
+use crate::shutdown::Shutdown;
+use crate::utils::{decode_base64_message, AppResult};
+
+/// 日志目录名，位于应用数据目录下，与前端 jsBridge/log.ts 保持一致
+pub const LOG_DIR_NAME: &str = "logs";
+
+/// 回报上传结果的请求超时时间
+const REPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 日志上传任务，由前端上传 zip 文件
+#[derive(Debug, Clone, Serialize)]
+pub struct LogUploadTask {
+    /// 任务 id，同一日期的多个上传请求各自使用独立的压缩包
+    pub id: String,
+    /// 日志日期
+    pub log_date: String,
+    /// 上传地址
+    pub log_url: String,
+    /// 压缩后的 zip 文件路径
+    pub archive: PathBuf,
+    /// 压缩的日志文件名
+    pub files: Vec<String>,
+    /// 压缩后的大小（字节）
+    pub size: u64,
+}
+
+/// 日志上传结果，回报给服务端
+#[derive(Debug, Clone, Serialize)]
+pub struct LogUploadResult {
+    /// 设备 id
+    pub device_id: String,
+    /// 日志日期
+    pub log_date: String,
+    /// 是否上传成功
+    pub success: bool,
+    /// 上传的日志文件名
+    pub files: Vec<String>,
+    /// 压缩后的大小（字节）
+    pub size: u64,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+impl LogUploadResult {
+    /// 上传失败的结果
+    fn failed(log_date: &str, error: String) -> Self {
+        Self {
+            device_id: current_device_id(),
+            log_date: log_date.to_string(),
+            success: false,
+            files: Vec::new(),
+            size: 0,
+            error: Some(error),
+        }
+    }
+}
+
+/// 等待前端上传的日志任务，按任务 id 索引
+#[derive(Debug, Default)]
+pub struct LogUploads {
+    pending: Mutex<HashMap<String, LogUploadTask>>,
+}
+
+impl LogUploads {
+    /// 创建日志上传状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记等待前端上传的任务
+    fn insert(&self, task: LogUploadTask) -> AppResult<()> {
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(task.id.clone(), task);
+        Ok(())
+    }
+
+    /// 取出上传结束的任务
+    fn take(&self, id: &str) -> AppResult<LogUploadTask> {
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(id)
+            .ok_or_else(|| format!("No pending log upload: {}", id))
+    }
+}
+
+/// 获取应用日志目录
+pub fn log_dir(app: &tauri::AppHandle) -> AppResult<PathBuf> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(LOG_DIR_NAME))
+}
+
+/// 校验日期格式是否为 YYYY-MM-DD
+pub fn is_valid_date(date: &str) -> bool {
+    let bytes = date.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// 判断文件名是否是指定日期的日志文件：`<date>.<类型>.log`
+///
+/// tracing-appender 按天滚动，不会产生 `.log.N` 形式的文件
+fn is_log_file_of(name: &str, date: &str) -> bool {
+    name.strip_prefix(date)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(".log"))
+        .is_some_and(|kind| !kind.is_empty())
+}
+
+/// 收集指定日期的日志文件，按文件名排序
+pub fn collect_log_files(dir: &Path, date: &str) -> AppResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| is_log_file_of(name, date))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 把日志文件压缩为 zip
+///
+/// 日志文件可能正在被写入，压缩的是读取那一刻的内容
+fn compress_logs(files: &[PathBuf], dest: &Path) -> AppResult<()> {
+    let out = File::create(dest).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(out));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for path in files {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("Invalid log file name: {}", path.display()))?;
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        std::io::copy(&mut file, &mut zip).map_err(|e| e.to_string())?;
+    }
+
+    zip.finish()
+        .map_err(|e| e.to_string())?
+        .flush()
+        .map_err(|e| e.to_string())
+}
+
+/// 获取设备 id，与 get_device_info 返回的相同
+fn current_device_id() -> String {
+    device_id::get_device_info()
+        .map(|info| info.device_id().to_string())
+        .unwrap_or_default()
+}
+
+/// 收集并压缩应用日志目录中的日志，压缩包放在应用缓存目录
+async fn prepare(app: &tauri::AppHandle, date: &str, url: &str) -> AppResult<LogUploadTask> {
+    let cache_dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
+    prepare_in(&log_dir(app)?, &cache_dir, date, url).await
+}
+
+/// 收集 `log_dir` 中指定日期的日志，压缩到 `cache_dir` 下以任务 id 命名的 zip 文件
+async fn prepare_in(
+    log_dir: &Path,
+    cache_dir: &Path,
+    date: &str,
+    url: &str,
+) -> AppResult<LogUploadTask> {
+    if !is_valid_date(date) {
+        return Err(format!("Invalid log date: {}", date));
+    }
+    if !endpoints::is_http_url(url) {
+        return Err(format!("Unsupported upload url: {}", url));
+    }
+
+    let files = collect_log_files(log_dir, date)?;
+    if files.is_empty() {
+        return Err(format!("No log files for {}", date));
+    }
+    let names = files
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str().map(String::from))
+        .collect();
+
+    std::fs::create_dir_all(cache_dir).map_err(|e| e.to_string())?;
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let archive = cache_dir.join(format!("logs-{}-{}.zip", date, id));
+
+    // 压缩是阻塞的文件操作，放到阻塞线程池中执行
+    let dest = archive.clone();
+    tauri::async_runtime::spawn_blocking(move || compress_logs(&files, &dest))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let size = std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+    Ok(LogUploadTask {
+        id,
+        log_date: date.to_string(),
+        log_url: url.to_string(),
+        archive,
+        files: names,
+        size,
+    })
+}
+
+/// 结束上传任务：删除压缩包并生成上传结果
+///
+/// # 参数
+/// - `error`: 上传失败的原因，None 表示上传成功
+fn finish(task: LogUploadTask, error: Option<String>) -> LogUploadResult {
+    let _ = std::fs::remove_file(&task.archive);
+    match error {
+        None => LogUploadResult {
+            device_id: current_device_id(),
+            log_date: task.log_date,
+            success: true,
+            files: task.files,
+            size: task.size,
+            error: None,
+        },
+        Some(e) => LogUploadResult::failed(&task.log_date, e),
+    }
+}
+
+/// 把上传结果 POST 到回报地址
+async fn post_result(url: &str, result: &LogUploadResult) -> AppResult<()> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .timeout(REPORT_TIMEOUT)
+        .json(result)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+/// 回报上传结果，未配置回报地址时只记录日志
+///
+/// 回报失败同样只记录日志，不影响返回给前端的收集或上传结果
+async fn report(app: &tauri::AppHandle, result: &LogUploadResult) {
+    tracing::info!(
+        log_date = %result.log_date,
+        success = result.success,
+        size = result.size,
+        error = ?result.error,
+        "日志上传结束"
+    );
+    let Some(url) = app.state::<Endpoints>().log_upload_report.clone() else {
+        tracing::warn!("未配置日志上传结果回报地址");
+        return;
+    };
+    if let Err(e) = post_result(&url, result).await {
+        tracing::warn!(error = %e, "回报日志上传结果失败");
+    }
+}
+
+/// 响应服务端的日志上传请求：收集并压缩日志
+///
+/// 前端拿到返回的任务后通过 tauri-plugin-upload 上传 zip 文件，
+/// 上传结束后调用 `finish_device_log_upload`；收集失败时直接回报失败，并返回收集失败的原因
+///
+/// # 参数
+/// - `data`: base64 编码的 EventDeviceLogUpload
+#[tauri::command]
+pub async fn prepare_device_log_upload(
+    app: tauri::AppHandle,
+    state: State<'_, LogUploads>,
+    data: String,
+) -> AppResult<LogUploadTask> {
+    // 压缩和回报较慢，登记后退出流程会等待它们结束
+    let _guard = app.state::<Shutdown>().register("log_upload_prepare");
+    let event: EventDeviceLogUpload = decode_base64_message(&data)?;
+    match prepare(&app, &event.log_date, &event.log_url).await {
+        Ok(task) => {
+            state.insert(task.clone())?;
+            Ok(task)
+        }
+        Err(e) => {
+            report(&app, &LogUploadResult::failed(&event.log_date, e.clone())).await;
+            Err(e)
+        }
+    }
+}
+
+/// 前端上传结束后调用：删除 zip 文件并回报上传结果
+///
+/// 回报失败不影响返回的上传结果，前端总能拿到本次上传是否成功
+///
+/// # 参数
+/// - `id`: `prepare_device_log_upload` 返回的任务 id
+/// - `error`: 上传失败的原因，成功时不传
+#[tauri::command]
+pub async fn finish_device_log_upload(
+    app: tauri::AppHandle,
+    state: State<'_, LogUploads>,
+    id: String,
+    error: Option<String>,
+) -> AppResult<LogUploadResult> {
+    let _guard = app.state::<Shutdown>().register("log_upload_finish");
+    let result = finish(state.take(&id)?, error);
+    report(&app, &result).await;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 日志上传本地桩服务
+    ///
+    /// 监听 127.0.0.1 上的一个端口，把收到的每个 POST 请求体按收到的顺序保存为文件并返回 200，
+    /// JSON 请求体（上传结果回报）保存为 `.json`，其他（日志压缩包）保存为 `.zip`
+    mod stub {
+        use std::path::{Path, PathBuf};
+
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::{TcpListener, TcpStream};
+
+        /// 启动桩服务，返回服务地址
+        pub async fn start(dir: PathBuf) -> String {
+            let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(serve(listener, dir));
+            format!("http://{}", addr)
+        }
+
+        /// 接受连接并逐个保存请求体，文件按收到的顺序编号
+        async fn serve(listener: TcpListener, dir: PathBuf) -> std::io::Result<()> {
+            tokio::fs::create_dir_all(&dir).await?;
+            let mut seq = 0u32;
+            loop {
+                let (stream, _) = listener.accept().await?;
+                seq += 1;
+                let dir = dir.clone();
+                tokio::spawn(async move { handle(stream, &dir, seq).await });
+            }
+        }
+
+        /// 读取一个 HTTP 请求，保存请求体并返回 200
+        async fn handle(stream: TcpStream, dir: &Path, seq: u32) -> std::io::Result<()> {
+            let mut reader = BufReader::new(stream);
+
+            // 读取请求行和请求头，只关心 Content-Length 和 Content-Type
+            let mut content_length = 0usize;
+            let mut is_json = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await? == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    let name = name.trim();
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    } else if name.eq_ignore_ascii_case("content-type") {
+                        is_json = value.trim().starts_with("application/json");
+                    }
+                }
+            }
+
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await?;
+            let ext = if is_json { "json" } else { "zip" };
+            tokio::fs::write(dir.join(format!("upload-{}.{}", seq, ext)), &body).await?;
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 11\r\nConnection: close\r\n\r\n{\"code\":0}\n";
+            reader.get_mut().write_all(response.as_bytes()).await
+        }
+    }
+
+    const DATE: &str = "2025-06-10";
+
+    /// 创建测试目录，logs 中包含指定日期的两个日志文件和其他无关文件
+    fn test_dirs(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("log_upload-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let logs = root.join(LOG_DIR_NAME);
+        std::fs::create_dir_all(&logs).unwrap();
+        std::fs::write(logs.join("2025-06-10.app.log"), "app log\n").unwrap();
+        std::fs::write(logs.join("2025-06-10.http.log"), "http log\n").unwrap();
+        std::fs::write(logs.join("2025-06-11.app.log"), "other day\n").unwrap();
+        std::fs::write(logs.join("2025-06-10.app.txt"), "not a log\n").unwrap();
+        (root.clone(), logs, root.join("cache"))
+    }
+
+    /// 读取 zip 中的文件名和内容
+    fn read_zip(bytes: &[u8]) -> Vec<(String, String)> {
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        (0..zip.len())
+            .map(|i| {
+                let mut file = zip.by_index(i).unwrap();
+                let mut content = String::new();
+                std::io::Read::read_to_string(&mut file, &mut content).unwrap();
+                (file.name().to_string(), content)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_log_files_of_date() {
+        assert!(is_log_file_of("2025-06-10.app.log", "2025-06-10"));
+        assert!(is_log_file_of("2025-06-10.http.log", "2025-06-10"));
+        assert!(!is_log_file_of("2025-06-10.app.log", "2025-06-11"));
+        assert!(!is_log_file_of("2025-06-10.log", "2025-06-10"));
+        assert!(!is_log_file_of("2025-06-10.app.log.1", "2025-06-10"));
+        assert!(!is_log_file_of("2025-06-10.app.txt", "2025-06-10"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn uploads_logs_to_stub() {
+        let (root, logs, cache) = test_dirs("flow");
+        let received = root.join("received");
+        let server = stub::start(received.clone()).await;
+        let uploads = LogUploads::new();
+
+        // 1. 收集并压缩日志
+        let upload_url = format!("{}/upload", server);
+        let task = prepare_in(&logs, &cache, DATE, &upload_url).await.unwrap();
+        assert_eq!(
+            task.files,
+            vec!["2025-06-10.app.log", "2025-06-10.http.log"]
+        );
+        assert_eq!(task.log_url, upload_url);
+        assert!(task.archive.starts_with(&cache));
+        let archive = std::fs::read(&task.archive).unwrap();
+        assert_eq!(archive.len() as u64, task.size);
+        assert_eq!(
+            read_zip(&archive),
+            vec![
+                ("2025-06-10.app.log".to_string(), "app log\n".to_string()),
+                ("2025-06-10.http.log".to_string(), "http log\n".to_string()),
+            ]
+        );
+        uploads.insert(task.clone()).unwrap();
+
+        // 2. 与前端的 upload 插件一样把压缩包 POST 到上传地址
+        let response = reqwest::Client::new()
+            .post(&task.log_url)
+            .header("Content-Type", "application/zip")
+            .body(archive.clone())
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        // 3. 结束任务并回报结果
+        let result = finish(uploads.take(&task.id).unwrap(), None);
+        assert!(!task.archive.exists());
+        assert!(result.success);
+        assert_eq!(result.log_date, DATE);
+        assert_eq!(result.files, task.files);
+        assert_eq!(result.size, task.size);
+        post_result(&format!("{}/report", server), &result)
+            .await
+            .unwrap();
+        assert!(uploads.take(&task.id).is_err());
+
+        assert_eq!(
+            std::fs::read(received.join("upload-1.zip")).unwrap(),
+            archive
+        );
+        let json = std::fs::read_to_string(received.join("upload-2.json")).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(body["log_date"], DATE);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["files"][1], "2025-06-10.http.log");
+        assert!(body["error"].is_null());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn overlapping_uploads_of_same_date_are_independent() {
+        let (root, logs, cache) = test_dirs("overlap");
+        let uploads = LogUploads::new();
+        let url = "http://127.0.0.1/upload";
+        let first = prepare_in(&logs, &cache, DATE, url).await.unwrap();
+        let second = prepare_in(&logs, &cache, DATE, url).await.unwrap();
+        assert_ne!(first.id, second.id);
+        assert_ne!(first.archive, second.archive);
+        uploads.insert(first.clone()).unwrap();
+        uploads.insert(second.clone()).unwrap();
+
+        // 第一个任务上传失败不影响第二个任务的压缩包
+        let result = finish(
+            uploads.take(&first.id).unwrap(),
+            Some("timeout".to_string()),
+        );
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("timeout"));
+        assert!(!first.archive.exists());
+        assert!(second.archive.exists());
+
+        let result = finish(uploads.take(&second.id).unwrap(), None);
+        assert!(result.success);
+        assert!(!second.archive.exists());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prepare_rejects_invalid_requests() {
+        let (root, logs, cache) = test_dirs("invalid");
+        let url = "http://127.0.0.1/upload";
+        assert!(prepare_in(&logs, &cache, "2025-6-10", url).await.is_err());
+        assert!(prepare_in(&logs, &cache, DATE, "file:///tmp/upload")
+            .await
+            .is_err());
+        assert_eq!(
+            prepare_in(&logs, &cache, "2025-06-12", url)
+                .await
+                .unwrap_err(),
+            "No log files for 2025-06-12"
+        );
+        assert!(prepare_in(&root.join("missing"), &cache, DATE, url)
+            .await
+            .is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn report_fails_on_unreachable_server() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let url = format!("http://{}/report", listener.local_addr().unwrap());
+        drop(listener);
+        let result = LogUploadResult::failed("2025-06-10", "test".to_string());
+        assert!(post_result(&url, &result).await.is_err());
+    }
+}
@@ -0,0 +1,527 @@
+/// 日志模块
+///
+/// 使用 tracing 记录带级别的结构化日志，同时输出到控制台和应用日志目录中按天滚动的文件：
+/// - 文件名为 `YYYY-MM-DD.app.log`，与前端 jsBridge/log.ts 的 `<日期>.<类型>.log` 命名一致，
+///   日志上传（log_upload）按日期收集时会一并带上
+/// - 只保留最近 `MAX_LOG_FILES` 天的日志文件，更早的在滚动时自动删除
+/// - 前端日志通过 `log_write` 命令写入同一个文件，target 为 "frontend"
+///
+/// 排查问题时可以在运行中调整日志过滤规则（按模块设置级别），
+/// 分页读取日志文件，并把新产生的日志实时推送给前端的日志页面；
+/// 最近的日志同时保留在内存中，供崩溃报告使用
+///
+/// 滚动日期按 UTC 计算，日志上传和按日期读取日志时的日期同样按 UTC 理解
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{Emitter, State};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Registry};
+
+use crate::log_upload;
+use crate::utils::AppResult;
+
+/// 日志文件名后缀，完整文件名为 `<日期>.app.log`
+const LOG_FILE_SUFFIX: &str = "app.log";
+
+/// 保留的日志文件数量（天）
+const MAX_LOG_FILES: usize = 14;
+
+/// 单次分页读取的最大行数
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// 分页读取时允许跳过的最大行数，分页窗口的内存占用以 MAX_OFFSET + MAX_PAGE_SIZE 行为上限
+const MAX_OFFSET: usize = 100_000;
+
+/// 当天的日志日期（UTC），与 tracing-appender 滚动日志文件的命名一致
+fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// 默认日志级别：调试构建记录 DEBUG 及以上，发布构建记录 INFO 及以上
+fn default_level() -> LevelFilter {
+    if cfg!(debug_assertions) {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    }
+}
+
+/// 日志控制状态
+///
+/// 由 `init` 创建，在 setup 阶段通过 `app.manage` 注册
+pub struct LogControl {
+    /// 日志目录
+    dir: PathBuf,
+    /// 过滤规则的重载句柄
+    filter: reload::Handle<Targets, Registry>,
+    /// 是否向前端推送新日志
+    streaming: Arc<AtomicBool>,
+}
+
+thread_local! {
+    /// 当前线程是否正在推送日志，防止推送过程中产生的日志再次触发推送
+    static EMITTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 把格式化好的日志行通过 "log-line" 事件推送给前端
+///
+/// fmt 层每条日志调用一次 `make_writer` 并一次性写入整行，写入器销毁时发送事件
+#[derive(Clone)]
+struct StreamMakeWriter {
+    app: tauri::AppHandle,
+    streaming: Arc<AtomicBool>,
+}
+
+/// 单条日志的写入器
+struct StreamWriter {
+    /// 未开启推送时为 None，写入的内容直接丢弃
+    app: Option<tauri::AppHandle>,
+    buf: Vec<u8>,
+}
+
+impl<'a> MakeWriter<'a> for StreamMakeWriter {
+    type Writer = StreamWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        let enabled = self.streaming.load(Ordering::Relaxed) && !EMITTING.with(Cell::get);
+        StreamWriter {
+            app: enabled.then(|| self.app.clone()),
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl std::io::Write for StreamWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.app.is_some() {
+            self.buf.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for StreamWriter {
+    fn drop(&mut self) {
+        let Some(app) = self.app.take() else { return };
+        let text = String::from_utf8_lossy(&self.buf);
+        let line = text.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        EMITTING.with(|flag| flag.set(true));
+        let _ = app.emit(
+            "log-line",
+            LogLine {
+                level: line_level(line).map(String::from),
+                line: line.to_string(),
+            },
+        );
+        EMITTING.with(|flag| flag.set(false));
+    }
+}
+
+/// 内存中保留的最近日志行数，崩溃报告从这里取最近的日志
+const RECENT_LINES: usize = 200;
+
+/// 最近的日志行
+///
+/// panic 钩子中不能再读日志文件或调用 tracing（可能正持有相关的锁），所以单独保留一份
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// 把格式化好的日志行写入最近日志的环形缓冲区
+struct RecentMakeWriter;
+
+/// 单条日志的写入器，销毁时把整行放入缓冲区
+struct RecentWriter {
+    buf: Vec<u8>,
+}
+
+impl<'a> MakeWriter<'a> for RecentMakeWriter {
+    type Writer = RecentWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentWriter { buf: Vec::new() }
+    }
+}
+
+impl std::io::Write for RecentWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentWriter {
+    fn drop(&mut self) {
+        let text = String::from_utf8_lossy(&self.buf);
+        let line = text.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line.to_string());
+        }
+    }
+}
+
+/// 最近的日志行，从旧到新排列
+///
+/// 供 panic 钩子使用：缓冲区正被占用（panic 发生在写日志的过程中）时返回空，不会阻塞
+pub fn recent_lines() -> Vec<String> {
+    match RECENT.try_lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// 初始化全局日志
+///
+/// 日志目录依赖应用路径解析，所以在 setup 阶段最先调用；
+/// 在此之前（插件初始化阶段）的日志会被丢弃
+pub fn init(app: &tauri::AppHandle) -> AppResult<LogControl> {
+    let dir = log_upload::log_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| e.to_string())?;
+
+    // 过滤规则放在最内层，对所有输出生效，运行中可以通过句柄替换
+    let (filter_layer, filter) = reload::Layer::new(Targets::new().with_default(default_level()));
+    let streaming = Arc::new(AtomicBool::new(false));
+
+    let file_layer = fmt::layer().with_ansi(false).with_writer(appender);
+    let console_layer = fmt::layer().with_writer(std::io::stdout);
+    let stream_layer = fmt::layer().with_ansi(false).with_writer(StreamMakeWriter {
+        app: app.clone(),
+        streaming: streaming.clone(),
+    });
+    let recent_layer = fmt::layer().with_ansi(false).with_writer(RecentMakeWriter);
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(file_layer)
+        .with(console_layer)
+        .with(stream_layer)
+        .with(recent_layer)
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        version = %app.package_info().version,
+        dir = %dir.display(),
+        "日志初始化完成"
+    );
+    Ok(LogControl {
+        dir,
+        filter,
+        streaming,
+    })
+}
+
+/// 一行日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// 日志级别，无法识别（如多行日志的后续行）时为 None
+    pub level: Option<String>,
+    /// 日志原文
+    pub line: String,
+}
+
+/// 一页日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    /// 读取的日志文件名
+    pub file: String,
+    /// 本页日志，从旧到新排列
+    pub lines: Vec<LogLine>,
+    /// 符合过滤条件的总行数
+    pub total: usize,
+}
+
+/// 从日志行中解析级别
+///
+/// fmt 层输出的格式为 `2025-06-10T08:00:00.123456Z  INFO target: message`，
+/// 时间戳之后的第一个单词就是级别
+fn line_level(line: &str) -> Option<&str> {
+    let mut parts = line.split_whitespace();
+    let timestamp = parts.next()?;
+    if !timestamp.starts_with(|c: char| c.is_ascii_digit()) || !timestamp.contains('T') {
+        return None;
+    }
+    parts
+        .next()
+        .filter(|level| matches!(*level, "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR"))
+}
+
+/// 级别从低到高的序号，用于“不低于某级别”的过滤
+fn level_rank(level: &str) -> u8 {
+    match level {
+        "TRACE" => 0,
+        "DEBUG" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        _ => 4,
+    }
+}
+
+impl LogControl {
+    /// 当前的过滤规则，如 `info,demo_lib::media_cache=debug`
+    pub fn filter(&self) -> AppResult<String> {
+        self.filter
+            .with_current(|targets| targets.to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 替换全部过滤规则
+    ///
+    /// # 参数
+    /// - `directives`: 逗号分隔的规则，如 `info,demo_lib::media_cache=debug`
+    pub fn set_filter(&self, directives: &str) -> AppResult<()> {
+        let targets: Targets = directives
+            .parse()
+            .map_err(|e| format!("Invalid log filter: {}", e))?;
+        self.filter.reload(targets).map_err(|e| e.to_string())
+    }
+
+    /// 设置单个模块的日志级别，不指定模块时设置默认级别
+    pub fn set_level(&self, module: Option<&str>, level: &str) -> AppResult<()> {
+        let level: LevelFilter = level
+            .parse()
+            .map_err(|_| format!("Invalid log level: {}", level))?;
+        self.filter
+            .modify(|targets| {
+                let current = std::mem::take(targets);
+                *targets = match module {
+                    Some(module) => current.with_target(module, level),
+                    None => current.with_default(level),
+                };
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    /// 获取要读取的日志文件：指定日期的文件，或最新的文件
+    fn log_file(&self, date: Option<&str>) -> AppResult<PathBuf> {
+        if let Some(date) = date {
+            if !log_upload::is_valid_date(date) {
+                return Err(format!("Invalid log date: {}", date));
+            }
+            return Ok(self.dir.join(format!("{}.{}", date, LOG_FILE_SUFFIX)));
+        }
+        std::fs::read_dir(&self.dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with(&format!(".{}", LOG_FILE_SUFFIX)))
+            })
+            .max()
+            .ok_or_else(|| "No log file".to_string())
+    }
+
+    /// 获取指定日期的日志文件，按文件名排序
+    ///
+    /// # 参数
+    /// - `date`: 日志日期（YYYY-MM-DD，UTC），不指定时为当天
+    pub fn files(&self, date: Option<&str>) -> AppResult<Vec<PathBuf>> {
+        let date = date.map(String::from).unwrap_or_else(today);
+        if !log_upload::is_valid_date(&date) {
+            return Err(format!("Invalid log date: {}", date));
+        }
+        log_upload::collect_log_files(&self.dir, &date)
+    }
+
+    /// 从文件末尾开始分页读取日志
+    ///
+    /// 多行日志（如 panic 信息）的后续行沿用上一行的级别参与过滤
+    ///
+    /// # 参数
+    /// - `date`: 日志日期（YYYY-MM-DD），不指定时读取最新的日志文件
+    /// - `offset`: 跳过最新的多少行，最多 `MAX_OFFSET`，不能超过符合条件的总行数
+    /// - `limit`: 本页最多返回多少行
+    /// - `min_level`: 只返回不低于这个级别的日志
+    pub fn read_page(
+        &self,
+        date: Option<&str>,
+        offset: usize,
+        limit: usize,
+        min_level: Option<&str>,
+    ) -> AppResult<LogPage> {
+        if offset > MAX_OFFSET {
+            return Err(format!(
+                "Log offset out of range: {} > {}",
+                offset, MAX_OFFSET
+            ));
+        }
+        let path = self.log_file(date)?;
+        let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+        let min_rank = min_level.map(|level| level_rank(&level.to_ascii_uppercase()));
+        let keep = offset + limit.min(MAX_PAGE_SIZE);
+
+        // 只保留最后 offset + limit 行，读取大文件时内存占用有上限；
+        // 不按 keep 预分配，日志文件较短时不会占用多余的内存
+        let mut window: VecDeque<LogLine> = VecDeque::new();
+        let mut total = 0;
+        let mut current_level: Option<String> = None;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            if let Some(level) = line_level(&line) {
+                current_level = Some(level.to_string());
+            }
+            let matches = match (min_rank, &current_level) {
+                (None, _) => true,
+                (Some(min), Some(level)) => level_rank(level) >= min,
+                (Some(_), None) => false,
+            };
+            if !matches {
+                continue;
+            }
+            total += 1;
+            if keep == 0 {
+                continue;
+            }
+            if window.len() == keep {
+                window.pop_front();
+            }
+            window.push_back(LogLine {
+                level: current_level.clone(),
+                line,
+            });
+        }
+
+        if offset > total {
+            return Err(format!("Log offset out of range: {} > {}", offset, total));
+        }
+        let end = window.len() - offset;
+        let lines = window.into_iter().take(end).collect();
+        Ok(LogPage {
+            file: path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            lines,
+            total,
+        })
+    }
+}
+
+/// 写入前端日志
+///
+/// 前端 jsBridge/log.ts 的 LogManager 通过这个命令把日志写入统一的日志文件
+///
+/// # 参数
+/// - `level`: 日志级别（TRACE、DEBUG、INFO、WARN、ERROR）
+/// - `log_type`: 前端日志类型，如 app、http
+/// - `module`: 前端传入的日志来源，如 HTTP_CLIENT
+/// - `message`: 日志内容
+#[tauri::command]
+pub fn log_write(level: String, log_type: String, module: String, message: String) {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => tracing::trace!(target: "frontend", log_type, module, "{}", message),
+        "DEBUG" => tracing::debug!(target: "frontend", log_type, module, "{}", message),
+        "WARN" => tracing::warn!(target: "frontend", log_type, module, "{}", message),
+        "ERROR" => tracing::error!(target: "frontend", log_type, module, "{}", message),
+        _ => tracing::info!(target: "frontend", log_type, module, "{}", message),
+    }
+}
+
+/// 获取当前的日志过滤规则
+#[tauri::command]
+pub fn get_log_filter(state: State<LogControl>) -> AppResult<String> {
+    state.filter()
+}
+
+/// 替换日志过滤规则
+///
+/// # 参数
+/// - `directives`: 逗号分隔的规则，如 `info,demo_lib::media_cache=debug`
+#[tauri::command]
+pub fn set_log_filter(state: State<LogControl>, directives: String) -> AppResult<String> {
+    state.set_filter(&directives)?;
+    tracing::info!(filter = %directives, "日志过滤规则已更新");
+    state.filter()
+}
+
+/// 设置模块的日志级别
+///
+/// # 参数
+/// - `module`: 模块路径，如 `demo_lib::media_cache` 或 `frontend`；不传时设置默认级别
+/// - `level`: trace、debug、info、warn、error 或 off
+#[tauri::command]
+pub fn set_log_level(
+    state: State<LogControl>,
+    module: Option<String>,
+    level: String,
+) -> AppResult<String> {
+    state.set_level(module.as_deref(), &level)?;
+    tracing::info!(module = ?module, level = %level, "日志级别已更新");
+    state.filter()
+}
+
+/// 从日志文件末尾分页读取日志
+///
+/// # 参数
+/// - `date`: 日志日期（YYYY-MM-DD），不传时读取最新的日志文件
+/// - `offset`: 跳过最新的多少行，第一页为 0，最多 100000 且不能超过总行数
+/// - `limit`: 每页行数，最多 1000
+/// - `min_level`: 最低级别，不传时不过滤
+#[tauri::command]
+pub fn read_log_lines(
+    state: State<LogControl>,
+    date: Option<String>,
+    offset: usize,
+    limit: usize,
+    min_level: Option<String>,
+) -> AppResult<LogPage> {
+    state.read_page(date.as_deref(), offset, limit, min_level.as_deref())
+}
+
+/// 获取指定日期的日志文件路径
+///
+/// 日志文件按 UTC 日期命名，前端按日期查找日志文件时使用这个命令，不要自行拼接文件名
+///
+/// # 参数
+/// - `date`: 日志日期（YYYY-MM-DD，UTC），不传时为当天
+#[tauri::command]
+pub fn list_log_files(state: State<LogControl>, date: Option<String>) -> AppResult<Vec<String>> {
+    Ok(state
+        .files(date.as_deref())?
+        .into_iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// 开启或关闭日志实时推送
+///
+/// 开启后每条新日志都会通过 "log-line" 事件发送给前端
+#[tauri::command]
+pub fn set_log_streaming(state: State<LogControl>, enabled: bool) {
+    state.streaming.store(enabled, Ordering::Relaxed);
+}
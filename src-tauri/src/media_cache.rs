@@ -0,0 +1,696 @@
+/// 媒体缓存模块
+///
+/// 聊天内容中引用了大量远程资源（表情图片、应用链接封面、勋章图标、语音和视频），
+/// 而 CSP 只允许 'self'。这个模块注册 `cache://` 自定义协议：
+/// WebView 请求 `cache://localhost/<base64url 编码的远程地址>` 时，
+/// 先从本地媒体缓存读取，未命中则通过 tauri-plugin-http 下载并写入缓存
+///
+/// 支持 Range 请求（音视频拖动进度需要），下载过程中已写入的部分即可按区间返回，
+/// 不需要等待整个文件下载完成。缓存目录按 LRU 淘汰以控制总大小
+///
+/// 远程地址来自消息内容，只允许下载公网地址：本机、局域网和链路本地地址
+/// （包括域名解析和重定向后的地址）一律拒绝，避免 WebView 借此访问内网服务
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose, Engine as _};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{Manager, State, Url};
+use tauri_plugin_http::reqwest::{self, dns, redirect};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{watch, Semaphore};
+
+use crate::disk_cache::{self, DiskCache};
+use crate::shutdown;
+use crate::utils::AppResult;
+
+/// 媒体缓存自定义协议的名称
+pub const MEDIA_SCHEME: &str = "cache";
+
+/// 媒体缓存目录的总大小上限：1GB
+const MEDIA_CACHE_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 单个媒体文件的大小上限：500MB
+const MEDIA_MAX_BYTES: u64 = 500 * 1024 * 1024;
+
+/// 单次响应最多返回的字节数：4MB
+///
+/// 自定义协议的响应体需要完整放在内存中，较大的文件只返回请求的开头部分，
+/// 音视频元素会继续通过 Range 请求读取后续内容
+const MEDIA_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 同时进行的下载数量上限，其余下载排队等待
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+/// 下载进度
+#[derive(Debug, Clone)]
+enum Progress {
+    /// 正在连接
+    Connecting,
+    /// 正在写入临时文件
+    Writing {
+        /// 已写入的字节数
+        written: u64,
+        /// 资源总长度，服务器没有返回 Content-Length 时为 None
+        total: Option<u64>,
+    },
+    /// 下载完成并已放入缓存
+    Done,
+    /// 下载失败
+    Failed(String),
+}
+
+/// 媒体缓存
+///
+/// 依赖应用缓存目录，所以在 setup 阶段创建并通过 `app.manage` 注册
+#[derive(Debug)]
+pub struct MediaCache {
+    /// 磁盘缓存，以远程地址的哈希为键
+    cache: DiskCache,
+    /// 正在下载的资源，同一个地址的并发请求共享一次下载
+    inflight: Mutex<HashMap<String, watch::Receiver<Progress>>>,
+    /// 只连接公网地址的 HTTP 客户端
+    client: reqwest::Client,
+    /// 限制同时进行的下载数量
+    downloads: Semaphore,
+}
+
+impl MediaCache {
+    /// 创建媒体缓存
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用缓存目录
+    pub fn new(app: &tauri::AppHandle) -> AppResult<Self> {
+        let dir = app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| e.to_string())?
+            .join("media");
+        let client = reqwest::Client::builder()
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if is_public_url(attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.error("redirect to a non-public address")
+                }
+            }))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            cache: DiskCache::new(dir, MEDIA_CACHE_MAX_BYTES)?,
+            inflight: Default::default(),
+            client,
+            downloads: Semaphore::new(MAX_CONCURRENT_DOWNLOADS),
+        })
+    }
+
+    /// 获取远程资源的下载进度，未缓存且没有在下载时在后台开始下载
+    ///
+    /// 下载不依赖发起请求的连接，请求被取消后仍会继续下载并放入缓存；
+    /// 下载任务登记到停止协调器，退出时中断，留下的临时文件在下次启动时清理
+    ///
+    /// # 返回值
+    /// - `Ok(None)`: 资源已在缓存中
+    /// - `Ok(Some(Receiver))`: 下载进度
+    fn watch(
+        &self,
+        app: &tauri::AppHandle,
+        url: &str,
+        key: &str,
+    ) -> AppResult<Option<watch::Receiver<Progress>>> {
+        let mut inflight = self.inflight.lock().map_err(|e| e.to_string())?;
+        if let Some(progress) = inflight.get(key) {
+            return Ok(Some(progress.clone()));
+        }
+        // 下载任务先放入缓存再移除记录，持有锁时检查不会漏掉刚完成的下载
+        if self.cache.contains(key) {
+            return Ok(None);
+        }
+
+        let (tx, rx) = watch::channel(Progress::Connecting);
+        inflight.insert(key.to_string(), rx.clone());
+        let handle = app.clone();
+        let url = url.to_string();
+        let key = key.to_string();
+        shutdown::spawn(app, "media_download", async move {
+            let state = handle.state::<MediaCache>();
+            let result = state.download(&url, &key, &tx).await;
+            if let Err(e) = &result {
+                tracing::warn!(url = %url, error = %e, "媒体缓存下载失败");
+                if let Ok(tmp) = state.cache.temp_path(&key) {
+                    let _ = tokio::fs::remove_file(tmp).await;
+                }
+            }
+            if let Ok(mut inflight) = state.inflight.lock() {
+                inflight.remove(&key);
+            }
+            let _ = tx.send(match result {
+                Ok(()) => Progress::Done,
+                Err(e) => Progress::Failed(e),
+            });
+        });
+        Ok(Some(rx))
+    }
+
+    /// 流式下载远程资源到临时文件，完成后放入缓存
+    ///
+    /// 同时进行的下载超过上限时先排队，排队期间进度保持为 Connecting。
+    /// 每写入一段内容都会更新下载进度，Range 请求可以在下载完成前读取已写入的部分
+    async fn download(&self, url: &str, key: &str, tx: &watch::Sender<Progress>) -> AppResult<()> {
+        let _permit = self.downloads.acquire().await.map_err(|e| e.to_string())?;
+        let mut response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        let total = response.content_length();
+        if total.is_some_and(|len| len > MEDIA_MAX_BYTES) {
+            return Err("Media too large".to_string());
+        }
+
+        let tmp = self.cache.temp_path(key)?;
+        let mut file = tokio::fs::File::create(&tmp)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut written: u64 = 0;
+        tx.send_replace(Progress::Writing { written, total });
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            if written + chunk.len() as u64 > MEDIA_MAX_BYTES {
+                return Err("Media too large".to_string());
+            }
+            // 写入完成后再更新进度，保证其他请求能从临时文件读到这部分内容
+            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+            file.flush().await.map_err(|e| e.to_string())?;
+            written += chunk.len() as u64;
+            tx.send_replace(Progress::Writing { written, total });
+        }
+        drop(file);
+
+        self.cache.commit(key)
+    }
+}
+
+/// 生成远程资源对应的 `cache://` 地址
+pub fn media_url(url: &str) -> String {
+    let encoded = general_purpose::URL_SAFE_NO_PAD.encode(url);
+    disk_cache::protocol_url(MEDIA_SCHEME, &encoded)
+}
+
+/// IP 地址是否为公网地址
+///
+/// 本机、局域网、链路本地、运营商级 NAT、组播和未指定地址都不是公网地址
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// 链接是否为指向公网地址的 http/https 链接
+///
+/// 域名只排除 localhost，解析后的地址由 PublicResolver 检查
+fn is_public_url(url: &Url) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    // IPv6 地址在 host_str 中带有方括号
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    }
+}
+
+/// 只返回公网地址的 DNS 解析器
+///
+/// 域名解析到本机或局域网地址时拒绝连接，重定向后的域名同样经过这里
+struct PublicResolver;
+
+impl dns::Resolve for PublicResolver {
+    fn resolve(&self, name: dns::Name) -> dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(
+                    format!("{} does not resolve to a public address", name.as_str()).into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as dns::Addrs)
+        })
+    }
+}
+
+/// 校验远程地址，只允许指向公网地址的 http 和 https 链接
+fn check_remote_url(url: &str) -> AppResult<()> {
+    match Url::parse(url) {
+        Ok(parsed) if is_public_url(&parsed) => Ok(()),
+        _ => Err(format!("Unsupported media url: {}", url)),
+    }
+}
+
+/// 从协议请求路径中解析远程地址，只允许指向公网地址的 http 和 https 链接
+fn parse_remote_url(path: &str) -> Option<String> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(path.trim_start_matches('/'))
+        .ok()?;
+    let url = String::from_utf8(bytes).ok()?;
+    check_remote_url(&url).ok()?;
+    Some(url)
+}
+
+/// 解析 Range 请求头
+///
+/// 支持 `bytes=start-end`、`bytes=start-` 和 `bytes=-suffix` 三种单区间形式
+///
+/// # 返回值
+/// - `Some(Ok((start, end)))`: 合法区间（包含两端）
+/// - `Some(Err(()))`: 区间无法满足，应返回 416
+/// - `None`: 没有 Range 头或格式不支持，返回完整内容
+fn parse_range(value: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    // 多区间请求较少见，按完整内容返回
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        (start, "") => (start.parse().ok()?, len.saturating_sub(1)),
+        (start, end) => {
+            let end: u64 = end.parse().ok()?;
+            (start.parse().ok()?, end.min(len.saturating_sub(1)))
+        }
+    };
+    if len == 0 || range.0 > range.1 || range.0 >= len {
+        return Some(Err(()));
+    }
+    Some(Ok(range))
+}
+
+/// 根据远程地址的扩展名推断 MIME 类型，无法推断时根据内容判断
+fn media_mime(url: &str, head: &[u8]) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let ext = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "mp3" => "audio/mpeg",
+        "m4a" | "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        "ogg" | "oga" => "audio/ogg",
+        "amr" => "audio/amr",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        _ => disk_cache::guess_mime(head),
+    }
+}
+
+/// 构造一个空响应
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+/// 计算本次响应返回的区间
+///
+/// 区间最长 MEDIA_CHUNK_BYTES 字节；没有 Range 头的请求在文件较大时同样只返回开头部分
+///
+/// # 返回值
+/// - `Ok(Some((start, end)))`: 返回 206 和该区间（包含两端）
+/// - `Ok(None)`: 返回 200 和完整内容
+/// - `Err(())`: 区间无法满足，返回 416
+fn response_range(range_header: Option<&str>, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    match range_header.and_then(|value| parse_range(value, len)) {
+        Some(Ok((start, end))) => Ok(Some((start, end.min(start + MEDIA_CHUNK_BYTES - 1)))),
+        Some(Err(())) => Err(()),
+        None if len > MEDIA_CHUNK_BYTES => Ok(Some((0, MEDIA_CHUNK_BYTES - 1))),
+        None => Ok(None),
+    }
+}
+
+/// 构造内容响应
+///
+/// # 参数
+/// - `url`: 远程地址，用于推断 MIME 类型
+/// - `len`: 资源总长度
+/// - `range`: `response_range` 的计算结果
+/// - `read`: 按区间（包含两端）读取内容
+fn content_response(
+    url: &str,
+    len: u64,
+    range: Result<Option<(u64, u64)>, ()>,
+    read: impl Fn(u64, u64) -> AppResult<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let head = read(0, 15.min(len.saturating_sub(1))).unwrap_or_default();
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, media_mime(url, &head))
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, "max-age=31536000, immutable");
+
+    let response = match range {
+        Ok(Some((start, end))) => match read(start, end) {
+            Ok(body) => builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, len),
+                )
+                .body(body),
+            Err(_) => return empty_response(StatusCode::NOT_FOUND),
+        },
+        Err(()) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Vec::new()),
+        Ok(None) if len == 0 => builder.status(StatusCode::OK).body(Vec::new()),
+        Ok(None) => match read(0, len - 1) {
+            Ok(body) => builder.status(StatusCode::OK).body(body),
+            Err(_) => return empty_response(StatusCode::NOT_FOUND),
+        },
+    };
+    response.unwrap_or_else(|_| empty_response(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+/// 等待下载进度满足本次请求
+///
+/// 服务器返回了资源总长度时，只需等到本次响应的区间写入临时文件；否则等待下载完成
+///
+/// # 返回值
+/// - `Ok(Some(total))`: 仍在下载，但本次响应的内容已经可以从临时文件读取
+/// - `Ok(None)`: 下载完成，从缓存读取
+/// - `Err(String)`: 下载失败
+async fn wait_readable(
+    progress: &mut watch::Receiver<Progress>,
+    range_header: Option<&str>,
+) -> AppResult<Option<u64>> {
+    let ready = progress
+        .wait_for(|progress| match progress {
+            Progress::Connecting | Progress::Writing { total: None, .. } => false,
+            Progress::Writing {
+                written,
+                total: Some(total),
+            } => match response_range(range_header, *total) {
+                Ok(Some((_, end))) => *written > end,
+                Ok(None) => *written >= *total && *total > 0,
+                // 416 不需要读取内容
+                Err(()) => true,
+            },
+            Progress::Done | Progress::Failed(_) => true,
+        })
+        .await
+        .map_err(|_| "Download aborted".to_string())?;
+    match &*ready {
+        Progress::Writing {
+            total: Some(total), ..
+        } => Ok(Some(*total)),
+        Progress::Failed(e) => Err(e.clone()),
+        _ => Ok(None),
+    }
+}
+
+/// 处理一次协议请求
+///
+/// 未缓存的资源在后台下载，请求只等待所需的部分写入临时文件，不需要等整个文件下载完成
+async fn serve(app: &tauri::AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(state) = app.try_state::<MediaCache>() else {
+        return empty_response(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let Some(url) = parse_remote_url(request.uri().path()) else {
+        return empty_response(StatusCode::BAD_REQUEST);
+    };
+    let key = DiskCache::hash(url.as_bytes());
+    let range_header = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+
+    let progress = match state.watch(app, &url, &key) {
+        Ok(progress) => progress,
+        Err(e) => {
+            tracing::warn!(url = %url, error = %e, "媒体缓存不可用");
+            return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if let Some(mut progress) = progress {
+        match wait_readable(&mut progress, range_header).await {
+            Ok(Some(total)) => {
+                let Ok(tmp) = state.cache.temp_path(&key) else {
+                    return empty_response(StatusCode::BAD_REQUEST);
+                };
+                // 读取时下载可能刚好完成，临时文件已经移动到缓存中
+                return content_response(
+                    &url,
+                    total,
+                    response_range(range_header, total),
+                    |start, end| {
+                        disk_cache::read_file_range(&tmp, start, end)
+                            .or_else(|_| state.cache.read_range(&key, start, end))
+                    },
+                );
+            }
+            Ok(None) => {}
+            Err(_) => return empty_response(StatusCode::BAD_GATEWAY),
+        }
+    }
+
+    let Ok(len) = state.cache.len(&key) else {
+        return empty_response(StatusCode::NOT_FOUND);
+    };
+    content_response(
+        &url,
+        len,
+        response_range(range_header, len),
+        |start, end| state.cache.read_range(&key, start, end),
+    )
+}
+
+/// 处理媒体缓存自定义协议请求
+pub fn handle_media_protocol(
+    ctx: tauri::UriSchemeContext<'_, tauri::Wry>,
+    request: Request<Vec<u8>>,
+    responder: tauri::UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let response = serve(&app, &request).await;
+        responder.respond(response);
+    });
+}
+
+/// 获取远程资源对应的本地缓存协议地址
+///
+/// 前端把聊天内容中的远程图片、音视频地址替换为返回的地址即可
+///
+/// # 参数
+/// - `url`: 远程资源地址（指向公网地址的 http 或 https 链接）
+#[tauri::command]
+pub fn get_cached_media_url(url: String) -> AppResult<String> {
+    check_remote_url(&url)?;
+    Ok(media_url(&url))
+}
+
+/// 获取媒体缓存当前占用的字节数
+#[tauri::command]
+pub fn get_media_cache_size(state: State<MediaCache>) -> AppResult<u64> {
+    state.cache.size()
+}
+
+/// 清空媒体缓存
+#[tauri::command]
+pub fn clear_media_cache(state: State<MediaCache>) -> AppResult<()> {
+    state.cache.clear()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(url: &str) -> bool {
+        is_public_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn allows_public_http_urls() {
+        assert!(public("https://cdn.example.com/emoji.png"));
+        assert!(public("http://example.com:8080/a.mp3?x=1"));
+        assert!(public("https://8.8.8.8/icon.png"));
+        assert!(public("https://[2001:4860:4860::8888]/icon.png"));
+    }
+
+    #[test]
+    fn rejects_local_and_private_hosts() {
+        for url in [
+            "http://localhost/",
+            "http://LOCALHOST./",
+            "http://api.localhost/",
+            "http://127.0.0.1:8080/",
+            "http://0x7f.1/",
+            "http://0.0.0.0/",
+            "http://10.1.2.3/",
+            "http://172.16.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://[::1]/",
+            "http://[::]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[::ffff:192.168.0.1]/",
+        ] {
+            assert!(!public(url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        assert!(!public("file:///etc/passwd"));
+        assert!(!public("ftp://example.com/a.png"));
+        assert!(check_remote_url("example.com/a.png").is_err());
+    }
+
+    #[test]
+    fn parses_only_public_remote_urls() {
+        let path = |url: &str| format!("/{}", general_purpose::URL_SAFE_NO_PAD.encode(url));
+        assert_eq!(
+            parse_remote_url(&path("https://example.com/a.png")).as_deref(),
+            Some("https://example.com/a.png")
+        );
+        assert_eq!(parse_remote_url(&path("http://192.168.1.1/a.png")), None);
+        assert_eq!(parse_remote_url(&path("javascript:alert(1)")), None);
+        assert_eq!(parse_remote_url("/not base64!"), None);
+    }
+
+    #[test]
+    fn parses_single_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range(" bytes= 10 - 19 ", 1000), Some(Ok((10, 19))));
+        assert_eq!(parse_range("bytes=500-", 1000), Some(Ok((500, 999))));
+        assert_eq!(parse_range("bytes=999-999", 1000), Some(Ok((999, 999))));
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Ok((900, 999))));
+        // 后缀长度超过资源长度时返回完整内容
+        assert_eq!(parse_range("bytes=-5000", 1000), Some(Ok((0, 999))));
+        assert_eq!(parse_range("bytes=-0", 1000), Some(Err(())));
+    }
+
+    #[test]
+    fn clamps_end_past_length() {
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some(Ok((900, 999))));
+        assert_eq!(
+            parse_range("bytes=0-18446744073709551615", 10),
+            Some(Ok((0, 9)))
+        );
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=1000-2000", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=10-5", 1000), Some(Err(())));
+        // 空资源的任何区间都无法满足
+        assert_eq!(parse_range("bytes=0-", 0), Some(Err(())));
+        assert_eq!(parse_range("bytes=-10", 0), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-0", 0), Some(Err(())));
+    }
+
+    #[test]
+    fn ignores_unsupported_range_headers() {
+        assert_eq!(parse_range("items=0-99", 1000), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("bytes=a-b", 1000), None);
+        assert_eq!(parse_range("bytes=5", 1000), None);
+        assert_eq!(parse_range("bytes=-", 1000), None);
+    }
+
+    #[test]
+    fn response_range_returns_small_files_whole() {
+        assert_eq!(response_range(None, 0), Ok(None));
+        assert_eq!(response_range(None, 1000), Ok(None));
+        assert_eq!(response_range(None, MEDIA_CHUNK_BYTES), Ok(None));
+        // 不支持的 Range 头按没有 Range 头处理
+        assert_eq!(response_range(Some("bytes=0-1,5-6"), 1000), Ok(None));
+    }
+
+    #[test]
+    fn response_range_clamps_to_chunk_size() {
+        let len = 10 * MEDIA_CHUNK_BYTES;
+        assert_eq!(
+            response_range(None, MEDIA_CHUNK_BYTES + 1),
+            Ok(Some((0, MEDIA_CHUNK_BYTES - 1)))
+        );
+        assert_eq!(
+            response_range(Some("bytes=0-"), len),
+            Ok(Some((0, MEDIA_CHUNK_BYTES - 1)))
+        );
+        assert_eq!(
+            response_range(Some("bytes=100-"), len),
+            Ok(Some((100, 100 + MEDIA_CHUNK_BYTES - 1)))
+        );
+        assert_eq!(
+            response_range(Some("bytes=-10"), len),
+            Ok(Some((len - 10, len - 1)))
+        );
+        assert_eq!(response_range(Some("bytes=10-19"), len), Ok(Some((10, 19))));
+    }
+
+    #[test]
+    fn response_range_reports_unsatisfiable_ranges() {
+        assert_eq!(response_range(Some("bytes=0-"), 0), Err(()));
+        assert_eq!(response_range(Some("bytes=1000-"), 1000), Err(()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolver_rejects_names_resolving_to_local_addresses() {
+        let name: dns::Name = "localhost".parse().unwrap();
+        assert!(dns::Resolve::resolve(&PublicResolver, name).await.is_err());
+    }
+}
@@ -0,0 +1,382 @@
+/// 系统公告模块
+///
+/// 保存服务端推送的系统公告（EventNotice），并负责：
+/// - 需要弹窗的公告（is_dialog）在独立的置顶公告窗口中逐条展示，用户确认后显示下一条
+/// - 公告到达失效时间（invalid_at）后自动隐藏
+/// - 通过 EventCommon 类型 1（{"notice_id": ...}）删除公告
+/// - 宣发公告（EventAnnounceNotice）需要弹窗时同样打开公告窗口
+///
+/// 公告列表保存在应用数据目录的 notices.json 中，重启后仍然可以查看
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+
+use crate::pb::*;
+use crate::shutdown;
+use crate::utils::{decode_base64_message, now_millis, to_millis, AppResult};
+use crate::window::{self, WindowSpec};
+
+/// 公告窗口的标签
+pub const NOTICE_WINDOW_LABEL: &str = "notice-window";
+
+/// 公告存储文件名
+const NOTICES_FILE: &str = "notices.json";
+
+/// 系统公告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notice {
+    /// 公告 id
+    pub id: u64,
+    /// 标题
+    pub title: String,
+    /// 内容
+    pub content: String,
+    /// 是否需要弹窗
+    pub is_dialog: bool,
+    /// 发送时间（毫秒）
+    pub send_at: i64,
+    /// 失效时间（毫秒），0 表示永不失效
+    pub invalid_at: i64,
+    /// 发布人
+    pub staff_name: String,
+    /// 发布主体
+    pub entity_name: String,
+    /// 弹窗是否已被用户确认
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+impl Notice {
+    /// 公告是否已经失效
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.invalid_at > 0 && self.invalid_at <= now
+    }
+
+    /// 是否还需要弹窗展示
+    fn needs_dialog(&self, now: i64) -> bool {
+        self.is_dialog && !self.acknowledged && !self.is_expired(now)
+    }
+}
+
+impl From<EventNotice> for Notice {
+    fn from(event: EventNotice) -> Self {
+        Self {
+            id: event.id,
+            title: event.title,
+            content: event.content,
+            is_dialog: event.is_dialog,
+            send_at: to_millis(event.send_at),
+            invalid_at: to_millis(event.invalid_at),
+            staff_name: event.staff_name,
+            entity_name: event.entity_name,
+            acknowledged: false,
+        }
+    }
+}
+
+/// 公告存储
+///
+/// 依赖应用数据目录，所以在 setup 阶段创建并通过 `app.manage` 注册
+#[derive(Debug)]
+pub struct NoticeStore {
+    /// 存储文件路径
+    path: PathBuf,
+    /// 所有未删除的公告
+    notices: Mutex<Vec<Notice>>,
+}
+
+impl NoticeStore {
+    /// 创建公告存储并从磁盘加载，已失效的公告在加载时丢弃
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用数据目录
+    pub fn new(app: &tauri::AppHandle) -> AppResult<Self> {
+        let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(NOTICES_FILE);
+
+        let now = now_millis();
+        let mut notices: Vec<Notice> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        notices.retain(|notice| !notice.is_expired(now));
+
+        Ok(Self {
+            path,
+            notices: Mutex::new(notices),
+        })
+    }
+
+    /// 新增或更新公告
+    ///
+    /// 服务端重复推送已确认的公告时保留确认状态，不会再次弹窗
+    pub fn upsert(&self, mut notice: Notice) -> AppResult<()> {
+        let mut notices = self.notices.lock().map_err(|e| e.to_string())?;
+        match notices.iter_mut().find(|n| n.id == notice.id) {
+            Some(existing) => {
+                notice.acknowledged |= existing.acknowledged;
+                *existing = notice;
+            }
+            None => notices.push(notice),
+        }
+        self.save(&notices)
+    }
+
+    /// 删除公告
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 已删除
+    /// - `Ok(false)`: 公告不存在
+    pub fn remove(&self, id: u64) -> AppResult<bool> {
+        let mut notices = self.notices.lock().map_err(|e| e.to_string())?;
+        let len = notices.len();
+        notices.retain(|notice| notice.id != id);
+        if notices.len() == len {
+            return Ok(false);
+        }
+        self.save(&notices)?;
+        Ok(true)
+    }
+
+    /// 标记公告弹窗已确认
+    pub fn acknowledge(&self, id: u64) -> AppResult<()> {
+        let mut notices = self.notices.lock().map_err(|e| e.to_string())?;
+        if let Some(notice) = notices.iter_mut().find(|n| n.id == id) {
+            notice.acknowledged = true;
+        }
+        self.save(&notices)
+    }
+
+    /// 获取未失效的公告，按发送时间从新到旧排序
+    pub fn active(&self) -> AppResult<Vec<Notice>> {
+        let now = now_millis();
+        let notices = self.notices.lock().map_err(|e| e.to_string())?;
+        let mut active: Vec<Notice> = notices
+            .iter()
+            .filter(|notice| !notice.is_expired(now))
+            .cloned()
+            .collect();
+        active.sort_by_key(|notice| std::cmp::Reverse(notice.send_at));
+        Ok(active)
+    }
+
+    /// 下一条需要弹窗展示的公告（最早发送的优先）
+    pub fn next_dialog(&self) -> AppResult<Option<Notice>> {
+        let now = now_millis();
+        let notices = self.notices.lock().map_err(|e| e.to_string())?;
+        Ok(notices
+            .iter()
+            .filter(|notice| notice.needs_dialog(now))
+            .min_by_key(|notice| notice.send_at)
+            .cloned())
+    }
+
+    /// 获取指定公告
+    pub fn get(&self, id: u64) -> AppResult<Option<Notice>> {
+        let notices = self.notices.lock().map_err(|e| e.to_string())?;
+        Ok(notices.iter().find(|notice| notice.id == id).cloned())
+    }
+
+    /// 所有需要定时失效的公告：(id, 失效时间)
+    fn expiries(&self) -> AppResult<Vec<(u64, i64)>> {
+        let notices = self.notices.lock().map_err(|e| e.to_string())?;
+        Ok(notices
+            .iter()
+            .filter(|notice| notice.invalid_at > 0)
+            .map(|notice| (notice.id, notice.invalid_at))
+            .collect())
+    }
+
+    /// 写入磁盘
+    fn save(&self, notices: &[Notice]) -> AppResult<()> {
+        let json = serde_json::to_string(notices).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| e.to_string())
+    }
+}
+
+/// 发送公告列表变化事件
+///
+/// 前端监听 "notices-changed" 事件后重新调用 list_notices 刷新公告列表
+fn emit_notices_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("notices-changed", ());
+}
+
+/// 打开公告窗口
+///
+/// 窗口已存在时显示并置顶，并通过 "notice-show" 事件告诉窗口要展示的内容
+///
+/// # 参数
+/// - `query`: 公告页面的查询参数，如 `id=100` 或 `announce_type=2`
+fn open_notice_window(app: &tauri::AppHandle, query: &str) -> AppResult<()> {
+    let spec = WindowSpec {
+        label: NOTICE_WINDOW_LABEL.to_string(),
+        title: "系统公告".to_string(),
+        url: format!("/notice?{}", query),
+        width: 420.0,
+        height: 360.0,
+        resizable: false,
+        dialog: true,
+        always_on_top: true,
+    };
+    let (_, created) = window::open_window(app, spec)?;
+    if !created {
+        app.emit_to(NOTICE_WINDOW_LABEL, "notice-show", query)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 关闭公告窗口
+fn close_notice_window(app: &tauri::AppHandle) {
+    let _ = window::close_window(app, NOTICE_WINDOW_LABEL);
+}
+
+/// 展示下一条需要弹窗的公告，没有时关闭公告窗口
+fn show_next_dialog(app: &tauri::AppHandle) -> AppResult<()> {
+    let state = app.state::<NoticeStore>();
+    match state.next_dialog()? {
+        Some(notice) => open_notice_window(app, &format!("id={}", notice.id)),
+        None => {
+            close_notice_window(app);
+            Ok(())
+        }
+    }
+}
+
+/// 在公告窗口中展示指定公告，用于从深度链接打开公告
+///
+/// 在 Windows 上同步命令中创建窗口会死锁，调用这个函数的命令必须是 async
+pub fn show_notice(app: &tauri::AppHandle, id: u64) -> AppResult<()> {
+    let notice = app
+        .state::<NoticeStore>()
+        .get(id)?
+        .filter(|notice| !notice.is_expired(now_millis()))
+        .ok_or_else(|| format!("公告不存在或已失效: {}", id))?;
+    open_notice_window(app, &format!("id={}", notice.id))
+}
+
+/// 启动公告失效定时器
+///
+/// 到达失效时间后刷新公告列表，如果公告正在弹窗展示则切换到下一条
+fn schedule_expiry(app: tauri::AppHandle, id: u64, invalid_at: i64) {
+    let delay = Duration::from_millis((invalid_at - now_millis()).max(0) as u64);
+    let handle = app.clone();
+    shutdown::spawn(&handle, "notice_expiry", async move {
+        tokio::time::sleep(delay).await;
+        let state = app.state::<NoticeStore>();
+        // 公告可能已被删除或更新了失效时间
+        let still_valid = state
+            .get(id)
+            .ok()
+            .flatten()
+            .is_some_and(|notice| notice.invalid_at == invalid_at);
+        if still_valid {
+            emit_notices_changed(&app);
+            let _ = show_next_dialog(&app);
+        }
+    });
+}
+
+/// 应用启动时恢复公告状态
+///
+/// 为未失效的公告重新启动失效定时器，并继续展示上次未确认的弹窗公告
+pub fn restore(app: &tauri::AppHandle) -> AppResult<()> {
+    let state = app.state::<NoticeStore>();
+    for (id, invalid_at) in state.expiries()? {
+        schedule_expiry(app.clone(), id, invalid_at);
+    }
+    if state.next_dialog()?.is_some() {
+        show_next_dialog(app)?;
+    }
+    Ok(())
+}
+
+/// 处理 EventCommon 类型 1：删除系统公告
+///
+/// # 参数
+/// - `data`: 事件内容，形如 `{"notice_id": 100}`
+pub fn handle_delete_event(app: &tauri::AppHandle, data: &str) -> AppResult<()> {
+    #[derive(Deserialize)]
+    struct DeleteNotice {
+        notice_id: u64,
+    }
+
+    let payload: DeleteNotice =
+        serde_json::from_str(data).map_err(|e| format!("Invalid notice event: {}", e))?;
+    let state = app.state::<NoticeStore>();
+    if state.remove(payload.notice_id)? {
+        emit_notices_changed(app);
+        show_next_dialog(app)?;
+    }
+    Ok(())
+}
+
+/// 应用系统公告推送
+///
+/// 可能需要创建公告窗口，在 Windows 上同步命令中创建窗口会死锁，所以使用异步命令
+///
+/// # 参数
+/// - `data`: base64 编码的 EventNotice
+#[tauri::command]
+pub async fn apply_notice(
+    state: State<'_, NoticeStore>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let notice: Notice = decode_base64_message::<EventNotice>(&data)?.into();
+    if notice.is_expired(now_millis()) {
+        return Ok(());
+    }
+
+    let (id, invalid_at, is_dialog) = (notice.id, notice.invalid_at, notice.is_dialog);
+    state.upsert(notice)?;
+    if invalid_at > 0 {
+        schedule_expiry(app.clone(), id, invalid_at);
+    }
+    emit_notices_changed(&app);
+
+    if is_dialog {
+        show_next_dialog(&app)?;
+    }
+    Ok(())
+}
+
+/// 应用宣发公告推送
+///
+/// 宣发公告只带类型，需要弹窗时打开公告窗口，由页面根据类型拉取具体内容
+///
+/// # 参数
+/// - `data`: base64 编码的 EventAnnounceNotice
+#[tauri::command]
+pub async fn apply_announce_notice(app: tauri::AppHandle, data: String) -> AppResult<()> {
+    let announce: EventAnnounceNotice = decode_base64_message(&data)?;
+    let _ = app.emit("announce-notice", announce.r#type);
+    if announce.is_dialog {
+        open_notice_window(&app, &format!("announce_type={}", announce.r#type))?;
+    }
+    Ok(())
+}
+
+/// 获取未失效的系统公告列表
+#[tauri::command]
+pub fn list_notices(state: State<NoticeStore>) -> AppResult<Vec<Notice>> {
+    state.active()
+}
+
+/// 确认公告弹窗，并展示下一条需要弹窗的公告
+///
+/// # 参数
+/// - `id`: 公告 id
+#[tauri::command]
+pub async fn acknowledge_notice(
+    state: State<'_, NoticeStore>,
+    app: tauri::AppHandle,
+    id: u64,
+) -> AppResult<()> {
+    state.acknowledge(id)?;
+    show_next_dialog(&app)
+}
@@ -0,0 +1,390 @@
+/// 新消息系统通知模块
+///
+/// 当主窗口隐藏或没有焦点时，为收到的新消息（MessagePushItem）弹出系统通知：
+/// - 同一会话的通知合并为一条，显示该会话累计的未读条数和最新一条消息的预览
+/// - 免打扰的会话、以及正在弹出窗口中查看的会话不弹通知
+/// - 遵循设置中的通知开关、是否显示消息内容和免打扰时段
+/// - 按消息类型截断或替换预览内容（图片、语音等只显示类型）
+/// - 点击通知时显示主窗口，并向前端发送 "open-conversation" 事件
+///
+/// Linux 上直接使用 notify-rust 以支持通知替换和点击回调。
+/// 其他平台通过 tauri-plugin-notification 显示，桌面端插件不提供点击回调，
+/// 改为记录最近弹出通知的会话：用户点击通知激活应用、主窗口获得焦点时打开该会话，
+/// 会话在此之前已经被查看过则不处理
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+
+use crate::chat_windows;
+use crate::pb::*;
+use crate::profiles::ProfileCache;
+use crate::settings::{self, NotificationSettings};
+use crate::utils::{decode_base64_message, AppResult};
+use crate::window;
+
+/// 文本消息预览的最大字符数
+const PREVIEW_MAX_CHARS: usize = 60;
+
+/// 会话标识
+///
+/// 群聊以群 id 标识，单聊以对方的用户 id 标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConversationKey {
+    /// 用户 id 或群 id
+    pub target_id: u64,
+    /// 是否为群聊
+    pub is_room: bool,
+}
+
+impl ConversationKey {
+    /// 根据消息确定它所属的会话
+    ///
+    /// 群消息属于 to_id 对应的群；单聊消息属于发送者
+    pub fn of_message(item: &MessagePushItem) -> Self {
+        if item.is_room {
+            Self {
+                target_id: item.to_id,
+                is_room: true,
+            }
+        } else {
+            Self {
+                target_id: item.from_id,
+                is_room: false,
+            }
+        }
+    }
+
+    /// 用作通知分组的字符串标识
+    #[cfg(not(target_os = "linux"))]
+    fn group_id(&self) -> String {
+        let kind = if self.is_room { "room" } else { "user" };
+        format!("{}-{}", kind, self.target_id)
+    }
+}
+
+/// 消息通知状态
+#[derive(Debug, Default)]
+pub struct Notifications {
+    /// 免打扰的会话
+    muted: Arc<Mutex<HashSet<ConversationKey>>>,
+    /// 每个会话自上次查看以来通知过的消息数
+    pending: Arc<Mutex<HashMap<ConversationKey, u32>>>,
+    /// 每个会话固定的通知 id，按首次通知的顺序分配，新通知会替换同一会话的旧通知
+    #[cfg(target_os = "linux")]
+    ids: Mutex<HashMap<ConversationKey, u32>>,
+    /// 正在等待点击的通知 id，每个 id 只保留一个等待线程
+    #[cfg(target_os = "linux")]
+    waiters: Mutex<HashSet<u32>>,
+    /// 最近弹出通知的会话，主窗口获得焦点时打开，代替通知的点击回调
+    #[cfg(not(target_os = "linux"))]
+    last_notified: Mutex<Option<ConversationKey>>,
+}
+
+impl Notifications {
+    /// 创建通知状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置会话是否免打扰
+    pub fn set_muted(&self, key: ConversationKey, muted: bool) -> AppResult<()> {
+        let mut set = self.muted.lock().map_err(|e| e.to_string())?;
+        if muted {
+            set.insert(key);
+        } else {
+            set.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// 判断会话是否免打扰
+    pub fn is_muted(&self, key: &ConversationKey) -> AppResult<bool> {
+        Ok(self.muted.lock().map_err(|e| e.to_string())?.contains(key))
+    }
+
+    /// 记录一条新通知，返回该会话累计的通知条数
+    fn record(&self, key: ConversationKey) -> AppResult<u32> {
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+        let count = pending.entry(key).or_insert(0);
+        *count += 1;
+        Ok(*count)
+    }
+
+    /// 清除会话累计的通知条数（用户已经查看了该会话）
+    pub fn clear(&self, key: &ConversationKey) -> AppResult<()> {
+        self.pending.lock().map_err(|e| e.to_string())?.remove(key);
+        Ok(())
+    }
+
+    /// 获取会话的通知 id，首次通知时分配
+    #[cfg(target_os = "linux")]
+    fn notification_id(&self, key: ConversationKey) -> AppResult<u32> {
+        let mut ids = self.ids.lock().map_err(|e| e.to_string())?;
+        let next = ids.len() as u32 + 1;
+        Ok(*ids.entry(key).or_insert(next))
+    }
+
+    /// 登记通知 id 的等待线程
+    ///
+    /// # 返回值
+    /// - `true`: 需要启动等待线程
+    /// - `false`: 该 id 已经有等待线程，替换后的通知被点击时同样由它处理
+    #[cfg(target_os = "linux")]
+    fn start_waiter(&self, id: u32) -> bool {
+        self.waiters
+            .lock()
+            .map(|mut waiters| waiters.insert(id))
+            .unwrap_or(false)
+    }
+
+    /// 等待线程结束（通知被点击或关闭）后注销
+    #[cfg(target_os = "linux")]
+    fn finish_waiter(&self, id: u32) {
+        if let Ok(mut waiters) = self.waiters.lock() {
+            waiters.remove(&id);
+        }
+    }
+
+    /// 记录最近弹出通知的会话
+    #[cfg(not(target_os = "linux"))]
+    fn set_last_notified(&self, key: ConversationKey) {
+        if let Ok(mut last) = self.last_notified.lock() {
+            *last = Some(key);
+        }
+    }
+
+    /// 取走最近弹出通知、且还没有被查看的会话
+    #[cfg(not(target_os = "linux"))]
+    fn take_last_notified(&self) -> AppResult<Option<ConversationKey>> {
+        let key = self.last_notified.lock().map_err(|e| e.to_string())?.take();
+        let pending = self.pending.lock().map_err(|e| e.to_string())?;
+        Ok(key.filter(|key| pending.contains_key(key)))
+    }
+}
+
+/// 根据消息类型生成通知中的预览内容
+///
+/// 只有文字消息显示原文（超长截断），其他类型显示占位文字
+pub fn preview_content(msg_type: i32, content: &str) -> String {
+    match msg_type {
+        1 => {
+            let mut chars = content.chars();
+            let preview: String = chars.by_ref().take(PREVIEW_MAX_CHARS).collect();
+            if chars.next().is_some() {
+                format!("{}…", preview)
+            } else {
+                preview
+            }
+        }
+        2 => "[图片]".to_string(),
+        3 => "[语音]".to_string(),
+        4 => "[视频]".to_string(),
+        5 => "[文件]".to_string(),
+        _ => "[新消息]".to_string(),
+    }
+}
+
+/// 判断当前是否需要弹出通知：主窗口隐藏或没有焦点
+fn should_notify(app: &tauri::AppHandle) -> bool {
+    match window::main_window(app) {
+        Some(window) => {
+            !window.is_visible().unwrap_or(false) || !window.is_focused().unwrap_or(false)
+        }
+        None => true,
+    }
+}
+
+/// 通知标题：发送者昵称，缺少资料时使用默认文字
+fn notification_title(app: &tauri::AppHandle, item: &MessagePushItem) -> String {
+    let nickname = app
+        .try_state::<ProfileCache>()
+        .and_then(|profiles| profiles.get(item.from_id).ok().flatten())
+        .map(|profile| profile.nickname)
+        .filter(|nickname| !nickname.is_empty());
+    match (nickname, item.is_room) {
+        (Some(nickname), true) => format!("{}（群聊）", nickname),
+        (Some(nickname), false) => nickname,
+        (None, true) => "群聊新消息".to_string(),
+        (None, false) => "新消息".to_string(),
+    }
+}
+
+/// 打开会话：显示主窗口并通知前端切换到该会话
+///
+/// 用于通知点击，也可由其他入口（如托盘）复用
+pub fn open_conversation(app: &tauri::AppHandle, key: ConversationKey) {
+    window::show_main_window(app);
+    if let Some(state) = app.try_state::<Notifications>() {
+        let _ = state.clear(&key);
+    }
+    let _ = app.emit("open-conversation", key);
+}
+
+/// 显示系统通知（Linux）
+///
+/// 使用固定的通知 id 替换同一会话的旧通知，并在后台线程等待用户点击
+#[cfg(target_os = "linux")]
+fn show_notification(app: &tauri::AppHandle, key: ConversationKey, title: &str, body: &str) {
+    let state = app.state::<Notifications>();
+    let id = match state.notification_id(key) {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::warn!(error = %e, "分配通知 id 失败");
+            return;
+        }
+    };
+    let identifier = app.config().identifier.clone();
+    let handle = notify_rust::Notification::new()
+        .appname(&identifier)
+        .summary(title)
+        .body(body)
+        .id(id)
+        .action("default", "查看")
+        .show();
+
+    match handle {
+        // 替换通知不会结束原来的等待，同一个 id 有多个等待线程时一次点击会重复打开会话
+        Ok(handle) if state.start_waiter(id) => {
+            let app = app.clone();
+            // wait_for_action 会阻塞直到通知被点击或关闭，放到独立线程中等待
+            std::thread::spawn(move || {
+                handle.wait_for_action(|action| {
+                    if action == "default" {
+                        open_conversation(&app, key);
+                    }
+                });
+                app.state::<Notifications>().finish_waiter(id);
+            });
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "显示消息通知失败"),
+    }
+}
+
+/// 显示系统通知（其他平台）
+///
+/// 插件没有点击回调，记录会话后由 `open_notified_conversation` 在主窗口获得焦点时打开
+#[cfg(not(target_os = "linux"))]
+fn show_notification(app: &tauri::AppHandle, key: ConversationKey, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    let result = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .group(key.group_id())
+        .show();
+    match result {
+        Ok(()) => app.state::<Notifications>().set_last_notified(key),
+        Err(e) => tracing::warn!(error = %e, "显示消息通知失败"),
+    }
+}
+
+/// 主窗口获得焦点时打开最近弹出通知的会话（Windows、macOS）
+///
+/// 通知没有点击回调，点击通知激活应用后主窗口获得焦点，以此代替点击回调。
+/// 每条通知只处理一次，会话已经被查看过（通知计数已清除）时不处理
+#[cfg(not(target_os = "linux"))]
+pub fn open_notified_conversation(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<Notifications>() else {
+        return;
+    };
+    match state.take_last_notified() {
+        Ok(Some(key)) => open_conversation(app, key),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "读取最近通知的会话失败"),
+    }
+}
+
+/// 为一条新消息弹出通知（如果需要）
+fn notify_message(
+    app: &tauri::AppHandle,
+    state: &Notifications,
+    prefs: &NotificationSettings,
+    item: &MessagePushItem,
+    current_user_id: u64,
+) -> AppResult<()> {
+    // 自己发出的消息（包括其他设备同步过来的）和已撤回的消息不通知
+    if item.from_id == current_user_id || item.cancelled_by != 0 {
+        return Ok(());
+    }
+    let key = ConversationKey::of_message(item);
+    if state.is_muted(&key)? || chat_windows::is_focused(app, &key) {
+        return Ok(());
+    }
+
+    let count = state.record(key)?;
+    let preview = if prefs.show_preview {
+        preview_content(item.r#type, &item.content)
+    } else {
+        "收到一条新消息".to_string()
+    };
+    let body = if count > 1 {
+        format!("[{}条] {}", count, preview)
+    } else {
+        preview
+    };
+    let title = notification_title(app, item);
+    show_notification(app, key, &title, &body);
+    Ok(())
+}
+
+/// 处理消息投递，把已弹出会话的消息转发给对应窗口，并为新消息弹出系统通知
+///
+/// 主窗口可见且有焦点、通知关闭或处于免打扰时段时不弹通知
+///
+/// # 参数
+/// - `data`: base64 编码的 MessagePush
+/// - `current_user_id`: 当前登录用户的 id，用于过滤自己发出的消息
+#[tauri::command]
+pub fn apply_message_push(
+    state: State<Notifications>,
+    app: tauri::AppHandle,
+    data: String,
+    current_user_id: u64,
+) -> AppResult<()> {
+    let push: MessagePush = decode_base64_message(&data)?;
+    chat_windows::route_messages(&app, &push, current_user_id)?;
+    if !should_notify(&app) {
+        return Ok(());
+    }
+    let settings = settings::current(&app);
+    if !settings.notifications.enabled || settings.do_not_disturb.is_active_now() {
+        return Ok(());
+    }
+    for item in &push.messages {
+        notify_message(&app, &state, &settings.notifications, item, current_user_id)?;
+    }
+    Ok(())
+}
+
+/// 设置会话免打扰
+///
+/// # 参数
+/// - `target_id`: 用户 id 或群 id
+/// - `is_room`: 是否为群聊
+/// - `muted`: 是否免打扰
+#[tauri::command]
+pub fn set_conversation_muted(
+    state: State<Notifications>,
+    target_id: u64,
+    is_room: bool,
+    muted: bool,
+) -> AppResult<()> {
+    state.set_muted(ConversationKey { target_id, is_room }, muted)
+}
+
+/// 清除会话的通知计数
+///
+/// 前端在用户打开会话时调用，下一条通知重新从 1 开始计数
+#[tauri::command]
+pub fn clear_conversation_notifications(
+    state: State<Notifications>,
+    target_id: u64,
+    is_room: bool,
+) -> AppResult<()> {
+    state.clear(&ConversationKey { target_id, is_room })
+}
@@ -81,3 +81,305 @@ pub struct SignInAck {
     #[prost(int64, tag = "4")]
     pub connect_time: i64,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MemberUpdate {
+    /// 自增id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// 群id
+    #[prost(uint64, tag = "2")]
+    pub chatroom_id: u64,
+    /// 成员id
+    #[prost(uint64, tag = "3")]
+    pub member_id: u64,
+    /// 管理员
+    #[prost(int32, tag = "4")]
+    pub level: i32,
+    /// 备注
+    #[prost(string, tag = "5")]
+    pub remark: ::prost::alloc::string::String,
+    /// 创建时间
+    #[prost(int64, tag = "6")]
+    pub created_at: i64,
+    /// 用户
+    #[prost(message, optional, tag = "7")]
+    pub user: ::core::option::Option<User>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct User {
+    /// 群id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// 昵称
+    #[prost(string, tag = "2")]
+    pub nickname: ::prost::alloc::string::String,
+    /// 头像
+    #[prost(string, tag = "3")]
+    pub avatar: ::prost::alloc::string::String,
+    /// 性别
+    #[prost(int32, tag = "4")]
+    pub gender: i32,
+    /// 状态
+    #[prost(string, tag = "5")]
+    pub sign: ::prost::alloc::string::String,
+    /// 创建时间
+    #[prost(int64, tag = "6")]
+    pub created_at: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageMemberChange {
+    /// 变更的成员
+    #[prost(uint64, repeated, tag = "1")]
+    pub user_ids: ::prost::alloc::vec::Vec<u64>,
+    /// 是进群还是退群
+    #[prost(bool, tag = "2")]
+    pub is_delete: bool,
+    /// 进群或退群的方式
+    #[prost(uint32, tag = "3")]
+    pub by_type: u32,
+    /// 谁邀请的？或者谁移除的？
+    #[prost(uint64, tag = "4")]
+    pub by_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageMemberLevelChange {
+    /// 变更的成员
+    #[prost(uint64, tag = "1")]
+    pub user_id: u64,
+    /// 变更后的群成员权限等级
+    #[prost(uint32, tag = "2")]
+    pub level: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageMemberBan {
+    /// 是所有人？禁言的情况下管理员不受影响，要禁言管理员，请先解除管理员的职务
+    #[prost(bool, tag = "1")]
+    pub is_all: bool,
+    /// 被禁言的成员，在is_all=false的情况下有效
+    #[prost(uint64, tag = "2")]
+    pub user_id: u64,
+    /// 禁言时长，毫秒，需要客户端传给后端, 等于0表示解除禁言
+    #[prost(int64, tag = "3")]
+    pub duration: i64,
+    /// 谁操作的
+    #[prost(uint64, tag = "4")]
+    pub by_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageChatroomMemberDelete {
+    /// 群id
+    #[prost(uint64, tag = "1")]
+    pub chatroom_id: u64,
+    /// 主动退出或被移除的成员
+    #[prost(uint64, repeated, tag = "2")]
+    pub user_ids: ::prost::alloc::vec::Vec<u64>,
+    /// 退群的方式,对应convention/进群退群方式
+    #[prost(uint32, tag = "3")]
+    pub by_type: u32,
+    /// 主动退出的成员或者谁移除的？
+    #[prost(uint64, tag = "4")]
+    pub by_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventUserInfoUpdate {
+    /// 群id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// 昵称
+    #[prost(string, tag = "2")]
+    pub nickname: ::prost::alloc::string::String,
+    /// 头像
+    #[prost(string, tag = "3")]
+    pub avatar: ::prost::alloc::string::String,
+    /// 性别
+    #[prost(int32, tag = "4")]
+    pub gender: i32,
+    /// 状态
+    #[prost(string, tag = "5")]
+    pub sign: ::prost::alloc::string::String,
+    /// 创建时间
+    #[prost(int64, tag = "6")]
+    pub created_at: i64,
+}
+/// 消息投递
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessagePush {
+    /// 消息id数组
+    #[prost(uint64, repeated, tag = "1")]
+    pub msg_ids: ::prost::alloc::vec::Vec<u64>,
+    /// 消息列表
+    #[prost(message, repeated, tag = "2")]
+    pub messages: ::prost::alloc::vec::Vec<MessagePushItem>,
+}
+/// 单条消息投递
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessagePushItem {
+    /// 消息id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// 消息类型
+    #[prost(int32, tag = "2")]
+    pub r#type: i32,
+    /// 消息内容
+    #[prost(string, tag = "3")]
+    pub content: ::prost::alloc::string::String,
+    /// 发送者id
+    #[prost(uint64, tag = "4")]
+    pub from_id: u64,
+    /// 用户id或者群组id
+    #[prost(uint64, tag = "5")]
+    pub to_id: u64,
+    /// 接收者类型
+    #[prost(bool, tag = "6")]
+    pub is_room: bool,
+    /// 消息发送时间戳，精确到毫秒
+    #[prost(int64, tag = "7")]
+    pub created_at: i64,
+    /// 消息额外信息
+    #[prost(string, tag = "8")]
+    pub meta: ::prost::alloc::string::String,
+    /// 被谁撤回了
+    #[prost(uint64, tag = "9")]
+    pub cancelled_by: u64,
+    /// 消息发送者的device_id
+    #[prost(uint64, tag = "10")]
+    pub device_id: u64,
+}
+/// 投递消息回执
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessagePushAck {
+    /// 消息id数组
+    #[prost(uint64, repeated, tag = "1")]
+    pub msg_ids: ::prost::alloc::vec::Vec<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventNotice {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub content: ::prost::alloc::string::String,
+    /// 是否弹窗：0 否 1 是
+    #[prost(bool, tag = "4")]
+    pub is_dialog: bool,
+    /// 发送时间
+    #[prost(int64, tag = "5")]
+    pub send_at: i64,
+    /// 失效时间
+    #[prost(int64, tag = "6")]
+    pub invalid_at: i64,
+    #[prost(string, tag = "7")]
+    pub staff_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub entity_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventAnnounceNotice {
+    /// 宣发类型
+    #[prost(int32, tag = "1")]
+    pub r#type: i32,
+    /// 是否弹窗提醒 true=弹框提醒
+    #[prost(bool, tag = "2")]
+    pub is_dialog: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventRemindNotePush {
+    /// 记事本记录ID
+    #[prost(uint64, tag = "1")]
+    pub note_id: u64,
+    /// uuid
+    #[prost(string, tag = "2")]
+    pub uuid: ::prost::alloc::string::String,
+    /// 内容
+    #[prost(string, tag = "3")]
+    pub content: ::prost::alloc::string::String,
+    /// 推送时间
+    #[prost(int64, tag = "4")]
+    pub push_time: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventDeviceLogUpload {
+    /// 日志日期 格式:2020-02-02
+    #[prost(string, tag = "1")]
+    pub log_date: ::prost::alloc::string::String,
+    /// 日志七牛地址
+    #[prost(string, tag = "2")]
+    pub log_url: ::prost::alloc::string::String,
+}
+/// 应用状态
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AppStatus {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub installed: bool,
+    #[prost(bool, tag = "3")]
+    pub running: bool,
+    /// 下发到前端用
+    #[prost(string, tag = "4")]
+    pub process_name: ::prost::alloc::string::String,
+    /// 前端上传拼多多用户的头像
+    #[prost(string, tag = "5")]
+    pub image: ::prost::alloc::string::String,
+}
+/// 应用列表
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EventAppsUpload {
+    #[prost(message, repeated, tag = "1")]
+    pub apps: ::prost::alloc::vec::Vec<AppStatus>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageAppLink {
+    /// 封面url
+    #[prost(string, tag = "1")]
+    pub cover_url: ::prost::alloc::string::String,
+    /// app url
+    #[prost(string, tag = "2")]
+    pub app_url: ::prost::alloc::string::String,
+    /// 标题
+    #[prost(string, tag = "3")]
+    pub title: ::prost::alloc::string::String,
+    /// 副标题
+    #[prost(string, tag = "4")]
+    pub sub_title: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageTask {
+    /// 消息 title = 任务提醒
+    #[prost(string, tag = "1")]
+    pub title: ::prost::alloc::string::String,
+    /// 主消息内容
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    /// 次要消息内容
+    #[prost(string, tag = "3")]
+    pub desc: ::prost::alloc::string::String,
+    /// 按钮链接
+    #[prost(string, tag = "4")]
+    pub link: ::prost::alloc::string::String,
+    /// 按钮移动端链接
+    #[prost(string, tag = "5")]
+    pub mobile_link: ::prost::alloc::string::String,
+    /// 关联的应用名称
+    #[prost(string, tag = "6")]
+    pub app_name: ::prost::alloc::string::String,
+}
@@ -0,0 +1,385 @@
+/// 用户资料缓存模块
+///
+/// 在 Rust 侧缓存 EventUserInfoUpdate 和 User 消息中的用户资料（昵称、头像、性别、状态）
+/// 头像只下载一次并保存到内容寻址的磁盘缓存中，通过自定义协议 `avatar://` 提供给 WebView，
+/// 这样各个窗口都不需要再通过 HTTP 重复拉取头像，也不需要放宽 CSP 的 `default-src 'self'`
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+
+use crate::disk_cache::{self, DiskCache};
+use crate::pb::*;
+use crate::shutdown;
+use crate::utils::{decode_base64_message, AppResult};
+
+/// 头像自定义协议的名称
+pub const AVATAR_SCHEME: &str = "avatar";
+
+/// 头像缓存目录的总大小上限：200MB
+const AVATAR_CACHE_MAX_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 单个头像文件的大小上限：5MB，超过的头像不缓存
+const AVATAR_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// 头像索引文件名，记录远程地址到内容哈希的映射
+const AVATAR_INDEX_FILE: &str = "index.json";
+
+/// 用户资料
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserProfile {
+    /// 用户 id
+    pub id: u64,
+    /// 昵称
+    pub nickname: String,
+    /// 头像的远程地址
+    pub avatar: String,
+    /// 性别
+    pub gender: i32,
+    /// 状态（个性签名）
+    pub sign: String,
+    /// 创建时间
+    pub created_at: i64,
+    /// 本地缓存的头像地址（`avatar://` 协议），尚未下载完成时为 None
+    pub local_avatar: Option<String>,
+}
+
+impl From<EventUserInfoUpdate> for UserProfile {
+    fn from(update: EventUserInfoUpdate) -> Self {
+        Self {
+            id: update.id,
+            nickname: update.nickname,
+            avatar: update.avatar,
+            gender: update.gender,
+            sign: update.sign,
+            created_at: update.created_at,
+            local_avatar: None,
+        }
+    }
+}
+
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            nickname: user.nickname,
+            avatar: user.avatar,
+            gender: user.gender,
+            sign: user.sign,
+            created_at: user.created_at,
+            local_avatar: None,
+        }
+    }
+}
+
+/// 头像索引：远程地址 -> 内容哈希
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AvatarIndex {
+    entries: HashMap<String, String>,
+}
+
+/// 用户资料缓存
+///
+/// 依赖应用缓存目录，所以在 setup 阶段创建并通过 `app.manage` 注册
+#[derive(Debug)]
+pub struct ProfileCache {
+    /// 用户资料，以用户 id 为键
+    profiles: Arc<Mutex<HashMap<u64, UserProfile>>>,
+    /// 头像索引
+    index: Arc<Mutex<AvatarIndex>>,
+    /// 正在下载的头像地址，避免同一头像被并发重复下载
+    downloading: Arc<Mutex<HashSet<String>>>,
+    /// 头像磁盘缓存
+    cache: DiskCache,
+}
+
+impl ProfileCache {
+    /// 创建用户资料缓存，并从磁盘加载头像索引
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用缓存目录
+    pub fn new(app: &tauri::AppHandle) -> AppResult<Self> {
+        let dir = app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| e.to_string())?
+            .join("avatars");
+        let cache = DiskCache::new(dir, AVATAR_CACHE_MAX_BYTES)?;
+
+        // 索引文件损坏时直接丢弃，头像会在下次使用时重新下载
+        let index = std::fs::read_to_string(cache.dir().join(AVATAR_INDEX_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            profiles: Default::default(),
+            index: Arc::new(Mutex::new(index)),
+            downloading: Default::default(),
+            cache,
+        })
+    }
+
+    /// 更新用户资料
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 头像地址发生了变化（或首次出现），需要下载新头像
+    /// - `Ok(false)`: 头像未变化
+    pub fn update(&self, profile: UserProfile) -> AppResult<bool> {
+        let mut profiles = self.profiles.lock().map_err(|e| e.to_string())?;
+        let avatar_changed = profiles
+            .get(&profile.id)
+            .is_none_or(|old| old.avatar != profile.avatar);
+        profiles.insert(profile.id, profile);
+        Ok(avatar_changed)
+    }
+
+    /// 获取用户资料，并填充本地头像地址
+    pub fn get(&self, user_id: u64) -> AppResult<Option<UserProfile>> {
+        let profiles = self.profiles.lock().map_err(|e| e.to_string())?;
+        let Some(mut profile) = profiles.get(&user_id).cloned() else {
+            return Ok(None);
+        };
+        profile.local_avatar = self.local_avatar(&profile.avatar)?;
+        Ok(Some(profile))
+    }
+
+    /// 移除用户资料缓存，同时丢弃该用户头像的索引，下次更新时会重新下载
+    pub fn invalidate(&self, user_id: u64) -> AppResult<()> {
+        let removed = self
+            .profiles
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&user_id);
+        if let Some(profile) = removed {
+            let mut index = self.index.lock().map_err(|e| e.to_string())?;
+            index.entries.remove(&profile.avatar);
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+
+    /// 获取远程头像对应的本地协议地址
+    ///
+    /// 如果索引中有记录但文件已经被大小上限淘汰，返回 None
+    fn local_avatar(&self, url: &str) -> AppResult<Option<String>> {
+        let index = self.index.lock().map_err(|e| e.to_string())?;
+        Ok(index
+            .entries
+            .get(url)
+            .filter(|hash| self.cache.contains(hash))
+            .map(|hash| disk_cache::protocol_url(AVATAR_SCHEME, hash)))
+    }
+
+    /// 判断头像是否需要下载
+    fn needs_download(&self, url: &str) -> AppResult<bool> {
+        if url.is_empty() {
+            return Ok(false);
+        }
+        Ok(self.local_avatar(url)?.is_none())
+    }
+
+    /// 保存下载好的头像，并记录到索引中
+    fn store_avatar(&self, url: &str, bytes: &[u8]) -> AppResult<()> {
+        let hash = self.cache.store(bytes)?;
+        let mut index = self.index.lock().map_err(|e| e.to_string())?;
+        index.entries.insert(url.to_string(), hash);
+        self.save_index(&index)
+    }
+
+    /// 把头像索引写入磁盘
+    fn save_index(&self, index: &AvatarIndex) -> AppResult<()> {
+        let json = serde_json::to_string(index).map_err(|e| e.to_string())?;
+        std::fs::write(self.cache.dir().join(AVATAR_INDEX_FILE), json).map_err(|e| e.to_string())
+    }
+
+    /// 所有使用指定头像地址的用户 id
+    fn users_with_avatar(&self, url: &str) -> AppResult<Vec<u64>> {
+        let profiles = self.profiles.lock().map_err(|e| e.to_string())?;
+        Ok(profiles
+            .values()
+            .filter(|profile| profile.avatar == url)
+            .map(|profile| profile.id)
+            .collect())
+    }
+}
+
+/// 发送用户资料变化事件
+///
+/// 前端监听 "user-profile-changed" 事件后重新调用 get_user_profile 获取最新资料
+fn emit_profile_changed(app: &tauri::AppHandle, user_id: u64) -> AppResult<()> {
+    app.emit("user-profile-changed", user_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 在后台下载头像
+///
+/// 下载完成后向所有使用该头像的用户发送资料变化事件
+fn spawn_avatar_download(app: tauri::AppHandle, url: String) {
+    let handle = app.clone();
+    shutdown::spawn(&handle, "avatar_download", async move {
+        let state = app.state::<ProfileCache>();
+
+        // 同一个地址只允许一个下载任务
+        let started = state
+            .downloading
+            .lock()
+            .map(|mut downloading| downloading.insert(url.clone()))
+            .unwrap_or(false);
+        if !started {
+            return;
+        }
+
+        let result = download_avatar(&url)
+            .await
+            .and_then(|bytes| state.store_avatar(&url, &bytes));
+
+        if let Ok(mut downloading) = state.downloading.lock() {
+            downloading.remove(&url);
+        }
+
+        match result {
+            Ok(()) => {
+                for user_id in state.users_with_avatar(&url).unwrap_or_default() {
+                    let _ = emit_profile_changed(&app, user_id);
+                }
+            }
+            Err(e) => tracing::warn!(url = %url, error = %e, "下载头像失败"),
+        }
+    });
+}
+
+/// 下载头像内容
+///
+/// 流式读取，超过 AVATAR_MAX_BYTES 立即中止，服务端没有返回 Content-Length 时同样生效
+async fn download_avatar(url: &str) -> AppResult<Vec<u8>> {
+    let mut response = tauri_plugin_http::reqwest::get(url)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > AVATAR_MAX_BYTES)
+    {
+        return Err("Avatar too large".to_string());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if bytes.len() + chunk.len() > AVATAR_MAX_BYTES {
+            return Err("Avatar too large".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// 更新资料并在需要时下载头像
+fn apply_profile(
+    app: &tauri::AppHandle,
+    state: &ProfileCache,
+    profile: UserProfile,
+) -> AppResult<()> {
+    let user_id = profile.id;
+    let url = profile.avatar.clone();
+    let avatar_changed = state.update(profile)?;
+    if avatar_changed && state.needs_download(&url)? {
+        spawn_avatar_download(app.clone(), url);
+    }
+    emit_profile_changed(app, user_id)
+}
+
+/// 处理头像自定义协议请求
+///
+/// 请求地址形如 `avatar://localhost/<sha256>`，直接从磁盘缓存读取文件返回
+pub fn handle_avatar_protocol(
+    ctx: tauri::UriSchemeContext<'_, tauri::Wry>,
+    request: tauri::http::Request<Vec<u8>>,
+    responder: tauri::UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let hash = request.uri().path().trim_start_matches('/').to_string();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let response = match app.try_state::<ProfileCache>() {
+            Some(state) => match state.cache.read(&hash) {
+                Ok(bytes) => tauri::http::Response::builder()
+                    .status(200)
+                    .header("Content-Type", disk_cache::guess_mime(&bytes))
+                    .header("Cache-Control", "max-age=31536000, immutable")
+                    .body(bytes),
+                Err(_) => tauri::http::Response::builder()
+                    .status(404)
+                    .body(Vec::new()),
+            },
+            None => tauri::http::Response::builder()
+                .status(503)
+                .body(Vec::new()),
+        };
+        responder.respond(response.unwrap_or_default());
+    });
+}
+
+/// 应用用户资料更新事件
+///
+/// # 参数
+/// - `data`: base64 编码的 EventUserInfoUpdate
+#[tauri::command]
+pub fn apply_user_info_update(
+    state: State<ProfileCache>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let update: EventUserInfoUpdate = decode_base64_message(&data)?;
+    apply_profile(&app, &state, update.into())
+}
+
+/// 应用 User 消息中的用户资料
+///
+/// # 参数
+/// - `data`: base64 编码的 User
+#[tauri::command]
+pub fn apply_user_profile(
+    state: State<ProfileCache>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let user: User = decode_base64_message(&data)?;
+    apply_profile(&app, &state, user.into())
+}
+
+/// 获取用户资料
+///
+/// 如果头像文件已被淘汰，会在后台重新下载，下载完成后发送 "user-profile-changed" 事件
+///
+/// # 参数
+/// - `user_id`: 用户 id
+///
+/// # 返回值
+/// - `Ok(Some(UserProfile))`: 缓存中的用户资料
+/// - `Ok(None)`: 缓存中没有该用户
+#[tauri::command]
+pub fn get_user_profile(
+    state: State<ProfileCache>,
+    app: tauri::AppHandle,
+    user_id: u64,
+) -> AppResult<Option<UserProfile>> {
+    let profile = state.get(user_id)?;
+    if let Some(profile) = &profile {
+        if profile.local_avatar.is_none() && !profile.avatar.is_empty() {
+            spawn_avatar_download(app, profile.avatar.clone());
+        }
+    }
+    Ok(profile)
+}
+
+/// 使用户资料缓存失效
+///
+/// # 参数
+/// - `user_id`: 用户 id
+#[tauri::command]
+pub fn invalidate_user_profile(state: State<ProfileCache>, user_id: u64) -> AppResult<()> {
+    state.invalidate(user_id)
+}
@@ -0,0 +1,517 @@
+/// 记事本提醒模块
+///
+/// 服务端通过 EventRemindNotePush 下发记事本提醒（note_id、uuid、内容、推送时间），
+/// 这个模块负责在推送时间到达时提醒用户，即使主窗口处于隐藏状态：
+/// - 待触发的提醒保存在应用数据目录的 reminders.json 中，重启后继续生效
+/// - 后台调度任务到点弹出系统通知并高亮托盘，错过的提醒（休眠、应用未运行）在恢复后立即补发
+/// - 按 uuid 去重，同一条提醒重复推送只会触发一次
+/// - 已触发的提醒保持“响铃”状态，直到用户稍后提醒或关闭
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+use tokio::sync::Notify;
+
+use crate::pb::*;
+use crate::shutdown;
+use crate::tray;
+use crate::utils::{decode_base64_message, now_millis, to_millis, AppResult};
+use crate::window;
+
+/// 提醒存储文件名
+const REMINDERS_FILE: &str = "reminders.json";
+
+/// 调度任务的最长检查间隔
+///
+/// 系统休眠期间定时器不一定计时，定期按系统时间重新检查，保证唤醒后及时补发
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 记住已关闭提醒 uuid 的最大数量，用于去重
+const MAX_SEEN_UUIDS: usize = 1000;
+
+/// 延迟超过这个时间触发的提醒视为“错过的提醒”（毫秒）
+const MISSED_THRESHOLD_MS: i64 = 60 * 1000;
+
+/// 记事本提醒
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    /// 记事本记录 id
+    pub note_id: u64,
+    /// 提醒的唯一标识
+    pub uuid: String,
+    /// 提醒内容
+    pub content: String,
+    /// 提醒时间（毫秒），稍后提醒会把它推后
+    pub push_time: i64,
+}
+
+impl From<EventRemindNotePush> for Reminder {
+    fn from(event: EventRemindNotePush) -> Self {
+        Self {
+            note_id: event.note_id,
+            uuid: event.uuid,
+            content: event.content,
+            push_time: to_millis(event.push_time),
+        }
+    }
+}
+
+/// 提醒列表，同时也是存储文件的内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Reminders {
+    /// 等待触发的提醒
+    pub pending: Vec<Reminder>,
+    /// 已经触发、用户还没有处理的提醒
+    pub ringing: Vec<Reminder>,
+    /// 已经关闭的提醒 uuid，只保留最近的一部分
+    #[serde(default)]
+    seen: VecDeque<String>,
+}
+
+impl Reminders {
+    /// 判断 uuid 对应的提醒是否已经存在或处理过
+    fn contains(&self, uuid: &str) -> bool {
+        self.pending.iter().any(|r| r.uuid == uuid)
+            || self.ringing.iter().any(|r| r.uuid == uuid)
+            || self.seen.iter().any(|seen| seen == uuid)
+    }
+
+    /// 从待触发和响铃列表中取出指定提醒
+    fn take(&mut self, uuid: &str) -> Option<Reminder> {
+        if let Some(index) = self.ringing.iter().position(|r| r.uuid == uuid) {
+            return Some(self.ringing.remove(index));
+        }
+        let index = self.pending.iter().position(|r| r.uuid == uuid)?;
+        Some(self.pending.remove(index))
+    }
+
+    /// 记录已关闭的 uuid
+    fn remember(&mut self, uuid: String) {
+        self.seen.push_back(uuid);
+        while self.seen.len() > MAX_SEEN_UUIDS {
+            self.seen.pop_front();
+        }
+    }
+}
+
+/// 提醒存储
+///
+/// 依赖应用数据目录，所以在 setup 阶段创建并通过 `app.manage` 注册
+#[derive(Debug)]
+pub struct ReminderStore {
+    /// 存储文件路径
+    path: PathBuf,
+    /// 提醒列表
+    reminders: Mutex<Reminders>,
+    /// 提醒列表变化时唤醒调度任务重新计算下一次触发时间
+    wake: Notify,
+}
+
+impl ReminderStore {
+    /// 创建提醒存储并从磁盘加载
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用数据目录
+    pub fn new(app: &tauri::AppHandle) -> AppResult<Self> {
+        let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(Self::load(dir.join(REMINDERS_FILE)))
+    }
+
+    /// 从存储文件加载提醒，文件不存在或无法解析时从空列表开始
+    fn load(path: PathBuf) -> Self {
+        let reminders: Reminders = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            reminders: Mutex::new(reminders),
+            wake: Notify::new(),
+        }
+    }
+
+    /// 添加提醒
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 已添加
+    /// - `Ok(false)`: 相同 uuid 的提醒已存在或已处理过，忽略
+    pub fn add(&self, reminder: Reminder) -> AppResult<bool> {
+        let mut reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        if reminder.uuid.is_empty() || reminders.contains(&reminder.uuid) {
+            return Ok(false);
+        }
+        reminders.pending.push(reminder);
+        self.save(&reminders)?;
+        self.wake.notify_one();
+        Ok(true)
+    }
+
+    /// 取出所有到点的提醒，并移动到响铃列表
+    fn take_due(&self, now: i64) -> AppResult<Vec<Reminder>> {
+        let mut reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        let (due, pending): (Vec<Reminder>, Vec<Reminder>) = reminders
+            .pending
+            .drain(..)
+            .partition(|r| r.push_time <= now);
+        reminders.pending = pending;
+        if !due.is_empty() {
+            reminders.ringing.extend(due.iter().cloned());
+            self.save(&reminders)?;
+        }
+        Ok(due)
+    }
+
+    /// 最早的待触发提醒时间
+    fn next_due(&self) -> AppResult<Option<i64>> {
+        let reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        Ok(reminders.pending.iter().map(|r| r.push_time).min())
+    }
+
+    /// 稍后提醒：把提醒重新放回待触发列表
+    ///
+    /// # 参数
+    /// - `uuid`: 提醒的唯一标识
+    /// - `push_time`: 新的提醒时间（毫秒）
+    pub fn snooze(&self, uuid: &str, push_time: i64) -> AppResult<()> {
+        let mut reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        let mut reminder = reminders
+            .take(uuid)
+            .ok_or_else(|| format!("Reminder not found: {}", uuid))?;
+        reminder.push_time = push_time;
+        reminders.pending.push(reminder);
+        self.save(&reminders)?;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// 关闭提醒，之后相同 uuid 的推送会被忽略
+    pub fn dismiss(&self, uuid: &str) -> AppResult<()> {
+        let mut reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        reminders.take(uuid);
+        reminders.remember(uuid.to_string());
+        self.save(&reminders)?;
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// 获取提醒列表
+    pub fn list(&self) -> AppResult<Reminders> {
+        let mut reminders = self.reminders.lock().map_err(|e| e.to_string())?.clone();
+        reminders.pending.sort_by_key(|r| r.push_time);
+        reminders.seen.clear();
+        Ok(reminders)
+    }
+
+    /// 最近触发的响铃提醒
+    fn latest_ringing(&self) -> AppResult<Option<Reminder>> {
+        let reminders = self.reminders.lock().map_err(|e| e.to_string())?;
+        Ok(reminders
+            .ringing
+            .iter()
+            .max_by_key(|r| r.push_time)
+            .cloned())
+    }
+
+    /// 写入磁盘
+    fn save(&self, reminders: &Reminders) -> AppResult<()> {
+        let json = serde_json::to_string(reminders).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| e.to_string())
+    }
+}
+
+/// 发送提醒列表变化事件
+///
+/// 前端监听 "reminders-changed" 事件后重新调用 list_reminders 刷新列表
+fn emit_reminders_changed(app: &tauri::AppHandle) {
+    let _ = app.emit("reminders-changed", ());
+}
+
+/// 根据响铃列表更新托盘高亮：有响铃提醒时显示最近一条，否则恢复未读数显示
+fn refresh_tray(app: &tauri::AppHandle) {
+    let latest = app.state::<ReminderStore>().latest_ringing().ok().flatten();
+    if let Err(e) = tray::set_tray_attention(app, latest.as_ref().map(|r| r.content.as_str())) {
+        tracing::warn!(error = %e, "更新托盘提醒状态失败");
+    }
+}
+
+/// 提醒通知的标题，延迟触发的提醒标记为已错过
+fn notification_title(reminder: &Reminder, now: i64) -> &'static str {
+    if now - reminder.push_time > MISSED_THRESHOLD_MS {
+        "记事本提醒（已错过）"
+    } else {
+        "记事本提醒"
+    }
+}
+
+/// 触发一条提醒：弹出系统通知、请求用户注意并通知前端
+fn fire(app: &tauri::AppHandle, reminder: &Reminder, now: i64) {
+    use tauri_plugin_notification::NotificationExt;
+
+    let result = app
+        .notification()
+        .builder()
+        .title(notification_title(reminder, now))
+        .body(&reminder.content)
+        .show();
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "显示提醒通知失败");
+    }
+
+    // 在任务栏或 Dock 上闪烁提示
+    if let Some(window) = window::main_window(app) {
+        let _ = window.request_user_attention(Some(tauri::UserAttentionType::Informational));
+    }
+    let _ = app.emit("reminder-fired", reminder);
+}
+
+/// 启动提醒调度任务
+///
+/// 启动时会立即补发应用未运行期间错过的提醒，并恢复托盘高亮
+pub fn start(app: &tauri::AppHandle) {
+    refresh_tray(app);
+
+    let app_handle = app.clone();
+    shutdown::spawn(app, "reminders", async move {
+        let app = app_handle;
+        let state = app.state::<ReminderStore>();
+        loop {
+            let now = now_millis();
+            match state.take_due(now) {
+                Ok(due) if !due.is_empty() => {
+                    for reminder in &due {
+                        fire(&app, reminder, now);
+                    }
+                    refresh_tray(&app);
+                    emit_reminders_changed(&app);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "检查到期提醒失败"),
+            }
+
+            // 等到下一条提醒到期，但不超过最长检查间隔；提醒列表变化时提前唤醒
+            let wait = state
+                .next_due()
+                .ok()
+                .flatten()
+                .map(|due| Duration::from_millis((due - now_millis()).max(0) as u64))
+                .map_or(MAX_CHECK_INTERVAL, |wait| wait.min(MAX_CHECK_INTERVAL));
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = state.wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// 应用记事本提醒推送
+///
+/// # 参数
+/// - `data`: base64 编码的 EventRemindNotePush
+#[tauri::command]
+pub fn apply_remind_note_push(
+    state: State<ReminderStore>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let reminder: Reminder = decode_base64_message::<EventRemindNotePush>(&data)?.into();
+    if state.add(reminder)? {
+        emit_reminders_changed(&app);
+    }
+    Ok(())
+}
+
+/// 获取待触发和响铃中的提醒
+#[tauri::command]
+pub fn list_reminders(state: State<ReminderStore>) -> AppResult<Reminders> {
+    state.list()
+}
+
+/// 稍后提醒
+///
+/// # 参数
+/// - `uuid`: 提醒的唯一标识
+/// - `minutes`: 推迟的分钟数
+#[tauri::command]
+pub fn snooze_reminder(
+    state: State<ReminderStore>,
+    app: tauri::AppHandle,
+    uuid: String,
+    minutes: u32,
+) -> AppResult<()> {
+    if minutes == 0 {
+        return Err("Snooze minutes must be greater than 0".to_string());
+    }
+    state.snooze(&uuid, now_millis() + i64::from(minutes) * 60 * 1000)?;
+    refresh_tray(&app);
+    emit_reminders_changed(&app);
+    Ok(())
+}
+
+/// 关闭提醒
+///
+/// # 参数
+/// - `uuid`: 提醒的唯一标识
+#[tauri::command]
+pub fn dismiss_reminder(
+    state: State<ReminderStore>,
+    app: tauri::AppHandle,
+    uuid: String,
+) -> AppResult<()> {
+    state.dismiss(&uuid)?;
+    refresh_tray(&app);
+    emit_reminders_changed(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60 * 1000;
+
+    /// 每个测试使用独立的存储文件
+    fn store_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("reminders-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(REMINDERS_FILE);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn reminder(uuid: &str, push_time: i64) -> Reminder {
+        Reminder {
+            note_id: 1,
+            uuid: uuid.to_string(),
+            content: format!("提醒 {}", uuid),
+            push_time,
+        }
+    }
+
+    fn uuids(reminders: &[Reminder]) -> Vec<&str> {
+        reminders.iter().map(|r| r.uuid.as_str()).collect()
+    }
+
+    #[test]
+    fn ignores_duplicate_and_dismissed_uuids() {
+        let store = ReminderStore::load(store_path("dedup"));
+        assert!(store.add(reminder("a", 1000)).unwrap());
+        assert!(!store.add(reminder("a", 2000)).unwrap());
+        assert!(!store.add(reminder("", 1000)).unwrap());
+
+        // 已经响铃的提醒再次推送同样忽略
+        store.take_due(1000).unwrap();
+        assert!(!store.add(reminder("a", 3000)).unwrap());
+
+        store.dismiss("a").unwrap();
+        assert!(!store.add(reminder("a", 4000)).unwrap());
+        let list = store.list().unwrap();
+        assert!(list.pending.is_empty() && list.ringing.is_empty());
+    }
+
+    #[test]
+    fn remembers_dismissed_uuids_across_restarts() {
+        let path = store_path("dedup-restart");
+        let store = ReminderStore::load(path.clone());
+        store.add(reminder("a", 1000)).unwrap();
+        store.dismiss("a").unwrap();
+        drop(store);
+
+        let store = ReminderStore::load(path);
+        assert!(!store.add(reminder("a", 1000)).unwrap());
+        assert!(store.add(reminder("b", 1000)).unwrap());
+    }
+
+    #[test]
+    fn forgets_oldest_dismissed_uuids() {
+        let store = ReminderStore::load(store_path("seen-limit"));
+        for i in 0..=MAX_SEEN_UUIDS {
+            store.dismiss(&i.to_string()).unwrap();
+        }
+        assert!(store.add(reminder("0", 1000)).unwrap());
+        assert!(!store.add(reminder("1", 1000)).unwrap());
+    }
+
+    #[test]
+    fn catches_up_overdue_reminders_after_restart() {
+        let path = store_path("catch-up");
+        let now = now_millis();
+        let store = ReminderStore::load(path.clone());
+        store.add(reminder("overdue", now - 10 * MINUTE)).unwrap();
+        store.add(reminder("due", now)).unwrap();
+        store.add(reminder("later", now + 10 * MINUTE)).unwrap();
+        drop(store);
+
+        // 重启后第一次检查就补发所有到期的提醒
+        let store = ReminderStore::load(path.clone());
+        let due = store.take_due(now).unwrap();
+        assert_eq!(uuids(&due), vec!["overdue", "due"]);
+        assert_eq!(store.next_due().unwrap(), Some(now + 10 * MINUTE));
+        assert!(store.take_due(now).unwrap().is_empty());
+        drop(store);
+
+        // 响铃中的提醒在重启后保留，不会重复触发
+        let store = ReminderStore::load(path);
+        let list = store.list().unwrap();
+        assert_eq!(uuids(&list.ringing), vec!["overdue", "due"]);
+        assert_eq!(uuids(&list.pending), vec!["later"]);
+        assert!(store.take_due(now).unwrap().is_empty());
+        assert_eq!(
+            store.latest_ringing().unwrap().map(|r| r.uuid),
+            Some("due".to_string())
+        );
+    }
+
+    #[test]
+    fn marks_late_reminders_as_missed() {
+        let now = now_millis();
+        assert_eq!(notification_title(&reminder("a", now), now), "记事本提醒");
+        assert_eq!(
+            notification_title(&reminder("a", now - MISSED_THRESHOLD_MS), now),
+            "记事本提醒"
+        );
+        assert_eq!(
+            notification_title(&reminder("a", now - 10 * MINUTE), now),
+            "记事本提醒（已错过）"
+        );
+    }
+
+    #[test]
+    fn snooze_moves_ringing_reminder_back_to_pending() {
+        let path = store_path("snooze");
+        let now = now_millis();
+        let store = ReminderStore::load(path.clone());
+        store.add(reminder("a", now)).unwrap();
+        assert_eq!(uuids(&store.take_due(now).unwrap()), vec!["a"]);
+
+        store.snooze("a", now + 5 * MINUTE).unwrap();
+        let list = store.list().unwrap();
+        assert!(list.ringing.is_empty());
+        assert_eq!(list.pending[0].push_time, now + 5 * MINUTE);
+        assert_eq!(store.latest_ringing().unwrap().map(|r| r.uuid), None);
+        assert_eq!(store.next_due().unwrap(), Some(now + 5 * MINUTE));
+        assert!(store.take_due(now + MINUTE).unwrap().is_empty());
+        drop(store);
+
+        // 稍后提醒的新时间同样保存到磁盘
+        let store = ReminderStore::load(path);
+        assert_eq!(uuids(&store.take_due(now + 5 * MINUTE).unwrap()), vec!["a"]);
+    }
+
+    #[test]
+    fn snooze_reschedules_pending_reminder_and_rejects_unknown_uuid() {
+        let store = ReminderStore::load(store_path("snooze-pending"));
+        store.add(reminder("a", 1000)).unwrap();
+        store.snooze("a", 5000).unwrap();
+        assert!(store.take_due(1000).unwrap().is_empty());
+        assert_eq!(uuids(&store.take_due(5000).unwrap()), vec!["a"]);
+
+        assert!(store.snooze("missing", 5000).is_err());
+        store.dismiss("a").unwrap();
+        assert!(store.snooze("a", 5000).is_err());
+    }
+}
@@ -0,0 +1,592 @@
+/// 群成员与角色缓存模块
+///
+/// 根据服务端下发的 MemberUpdate 以及各类群成员消息（进退群、权限变更、禁言、移除成员）
+/// 在 Rust 侧维护每个群的成员列表、管理员等级和禁言状态
+/// 禁言带有时长（毫秒），到期后由定时任务自动解除并通知前端，
+/// 前端可以据此禁用输入框，而不必自己维护计时器
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, State};
+
+use crate::pb::*;
+use crate::shutdown;
+use crate::utils::{decode_base64_message, now_millis, AppResult};
+
+/// 群成员信息
+///
+/// 通过 Serialize 直接返回给前端
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoomMember {
+    /// 成员用户 id
+    pub member_id: u64,
+    /// 群内权限等级，0 为普通成员，大于 0 为管理员
+    pub level: i32,
+    /// 群内备注
+    pub remark: String,
+    /// 进群时间（毫秒）
+    pub created_at: i64,
+    /// 昵称（来自 MemberUpdate.user，可能为空）
+    pub nickname: String,
+    /// 头像地址（来自 MemberUpdate.user，可能为空）
+    pub avatar: String,
+    /// 单独禁言的到期时间（毫秒），None 表示未被单独禁言
+    pub muted_until: Option<i64>,
+}
+
+/// 单个群的状态
+#[derive(Debug, Default)]
+struct RoomState {
+    /// 成员列表，以成员 id 为键
+    members: HashMap<u64, RoomMember>,
+    /// 全员禁言的到期时间（毫秒），None 表示未开启全员禁言
+    mute_all_until: Option<i64>,
+}
+
+impl RoomState {
+    /// 获取成员，如果不存在则插入一个只有 id 的占位成员
+    ///
+    /// 进群消息只带用户 id，资料会在后续的 MemberUpdate 中补全
+    fn member_mut(&mut self, member_id: u64) -> &mut RoomMember {
+        self.members.entry(member_id).or_insert_with(|| RoomMember {
+            member_id,
+            ..Default::default()
+        })
+    }
+}
+
+/// 禁言状态变化事件的负载
+#[derive(Debug, Clone, Serialize)]
+struct RoomMuteChanged {
+    room_id: u64,
+    /// 被禁言的成员，None 表示全员禁言
+    user_id: Option<u64>,
+    /// 禁言到期时间（毫秒），None 表示已解除
+    muted_until: Option<i64>,
+}
+
+/// 全局群成员状态
+///
+/// 与 UnreadCount 一样通过 Arc<Mutex<T>> 在各命令之间共享
+#[derive(Debug, Default)]
+pub struct RoomMembers {
+    rooms: Arc<Mutex<HashMap<u64, RoomState>>>,
+}
+
+impl RoomMembers {
+    /// 创建新的群成员缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取群成员列表，按权限等级从高到低、进群时间从早到晚排序
+    pub fn members(&self, room_id: u64) -> AppResult<Vec<RoomMember>> {
+        let rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let mut members: Vec<RoomMember> = rooms
+            .get(&room_id)
+            .map(|room| room.members.values().cloned().collect())
+            .unwrap_or_default();
+        members.sort_by(|a, b| b.level.cmp(&a.level).then(a.created_at.cmp(&b.created_at)));
+        Ok(members)
+    }
+
+    /// 判断成员在群内是否处于禁言状态
+    ///
+    /// 单独禁言对所有成员生效；全员禁言时管理员不受影响
+    pub fn is_muted(&self, room_id: u64, user_id: u64) -> AppResult<bool> {
+        let rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let Some(room) = rooms.get(&room_id) else {
+            return Ok(false);
+        };
+        let now = now_millis();
+        let member = room.members.get(&user_id);
+
+        // 单独禁言
+        if member
+            .and_then(|m| m.muted_until)
+            .is_some_and(|until| until > now)
+        {
+            return Ok(true);
+        }
+
+        // 全员禁言，管理员除外
+        let is_admin = member.is_some_and(|m| m.level > 0);
+        Ok(!is_admin && room.mute_all_until.is_some_and(|until| until > now))
+    }
+
+    /// 应用 MemberUpdate：新增或更新一个成员的资料和权限
+    pub fn apply_member_update(&self, update: MemberUpdate) -> AppResult<()> {
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let room = rooms.entry(update.chatroom_id).or_default();
+        let member = room.member_mut(update.member_id);
+        member.level = update.level;
+        member.remark = update.remark;
+        member.created_at = update.created_at;
+        if let Some(user) = update.user {
+            member.nickname = user.nickname;
+            member.avatar = user.avatar;
+        }
+        Ok(())
+    }
+
+    /// 应用进群/退群消息
+    pub fn apply_member_change(&self, room_id: u64, change: MessageMemberChange) -> AppResult<()> {
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let room = rooms.entry(room_id).or_default();
+        for user_id in change.user_ids {
+            if change.is_delete {
+                room.members.remove(&user_id);
+            } else {
+                room.member_mut(user_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 应用成员权限等级变更消息
+    pub fn apply_level_change(
+        &self,
+        room_id: u64,
+        change: MessageMemberLevelChange,
+    ) -> AppResult<()> {
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let room = rooms.entry(room_id).or_default();
+        room.member_mut(change.user_id).level = change.level as i32;
+        Ok(())
+    }
+
+    /// 应用移除成员消息
+    pub fn apply_member_delete(&self, delete: MessageChatroomMemberDelete) -> AppResult<()> {
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        if let Some(room) = rooms.get_mut(&delete.chatroom_id) {
+            for user_id in delete.user_ids {
+                room.members.remove(&user_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// 应用禁言消息
+    ///
+    /// # 返回值
+    /// - `Ok(Some(until))`: 开启禁言，返回到期时间（毫秒）
+    /// - `Ok(None)`: 解除禁言
+    pub fn apply_ban(&self, room_id: u64, ban: &MessageMemberBan) -> AppResult<Option<i64>> {
+        let until = (ban.duration > 0).then(|| now_millis() + ban.duration);
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let room = rooms.entry(room_id).or_default();
+        if ban.is_all {
+            room.mute_all_until = until;
+        } else {
+            room.member_mut(ban.user_id).muted_until = until;
+        }
+        Ok(until)
+    }
+
+    /// 禁言到期时解除禁言
+    ///
+    /// 只有当记录的到期时间仍然是 `until` 时才解除，
+    /// 避免在此期间重新禁言（延长时长）的记录被旧的定时任务清掉
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 已解除禁言
+    /// - `Ok(false)`: 禁言已被更新或解除，无需处理
+    fn expire_ban(&self, room_id: u64, user_id: Option<u64>, until: i64) -> AppResult<bool> {
+        let mut rooms = self.rooms.lock().map_err(|e| e.to_string())?;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return Ok(false);
+        };
+        let slot = match user_id {
+            Some(user_id) => match room.members.get_mut(&user_id) {
+                Some(member) => &mut member.muted_until,
+                None => return Ok(false),
+            },
+            None => &mut room.mute_all_until,
+        };
+        if *slot == Some(until) {
+            *slot = None;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// 发送群成员变化事件
+///
+/// 前端监听 "room-members-changed" 事件后重新调用 get_room_members 刷新成员列表
+fn emit_members_changed(app: &tauri::AppHandle, room_id: u64) -> AppResult<()> {
+    app.emit("room-members-changed", room_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 发送禁言状态变化事件
+fn emit_mute_changed(
+    app: &tauri::AppHandle,
+    room_id: u64,
+    user_id: Option<u64>,
+    muted_until: Option<i64>,
+) -> AppResult<()> {
+    let payload = RoomMuteChanged {
+        room_id,
+        user_id,
+        muted_until,
+    };
+    app.emit("room-mute-changed", payload)
+        .map_err(|e| e.to_string())
+}
+
+/// 启动禁言到期定时器
+///
+/// 在异步运行时中等待禁言时长，到期后解除禁言并通知前端
+fn schedule_ban_expiry(app: tauri::AppHandle, room_id: u64, user_id: Option<u64>, until: i64) {
+    let delay = Duration::from_millis((until - now_millis()).max(0) as u64);
+    let handle = app.clone();
+    shutdown::spawn(&handle, "ban_expiry", async move {
+        tokio::time::sleep(delay).await;
+        let state = app.state::<RoomMembers>();
+        if let Ok(true) = state.expire_ban(room_id, user_id, until) {
+            let _ = emit_mute_changed(&app, room_id, user_id, None);
+        }
+    });
+}
+
+/// 应用 MemberUpdate 推送
+///
+/// # 参数
+/// - `data`: base64 编码的 MemberUpdate
+#[tauri::command]
+pub fn apply_member_update(
+    state: State<RoomMembers>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let update: MemberUpdate = decode_base64_message(&data)?;
+    let room_id = update.chatroom_id;
+    state.apply_member_update(update)?;
+    emit_members_changed(&app, room_id)
+}
+
+/// 应用进群/退群消息
+///
+/// # 参数
+/// - `room_id`: 消息所在的群 id（MessagePushItem.to_id）
+/// - `data`: base64 编码的 MessageMemberChange
+#[tauri::command]
+pub fn apply_member_change(
+    state: State<RoomMembers>,
+    app: tauri::AppHandle,
+    room_id: u64,
+    data: String,
+) -> AppResult<()> {
+    let change: MessageMemberChange = decode_base64_message(&data)?;
+    state.apply_member_change(room_id, change)?;
+    emit_members_changed(&app, room_id)
+}
+
+/// 应用成员权限等级变更消息
+///
+/// # 参数
+/// - `room_id`: 消息所在的群 id
+/// - `data`: base64 编码的 MessageMemberLevelChange
+#[tauri::command]
+pub fn apply_member_level_change(
+    state: State<RoomMembers>,
+    app: tauri::AppHandle,
+    room_id: u64,
+    data: String,
+) -> AppResult<()> {
+    let change: MessageMemberLevelChange = decode_base64_message(&data)?;
+    state.apply_level_change(room_id, change)?;
+    emit_members_changed(&app, room_id)
+}
+
+/// 应用禁言/解除禁言消息
+///
+/// 开启禁言时会启动一个到期定时器，到期后自动解除并发送 "room-mute-changed" 事件
+///
+/// # 参数
+/// - `room_id`: 消息所在的群 id
+/// - `data`: base64 编码的 MessageMemberBan
+#[tauri::command]
+pub fn apply_member_ban(
+    state: State<RoomMembers>,
+    app: tauri::AppHandle,
+    room_id: u64,
+    data: String,
+) -> AppResult<()> {
+    let ban: MessageMemberBan = decode_base64_message(&data)?;
+    let user_id = (!ban.is_all).then_some(ban.user_id);
+    let until = state.apply_ban(room_id, &ban)?;
+
+    if let Some(until) = until {
+        schedule_ban_expiry(app.clone(), room_id, user_id, until);
+    }
+    emit_mute_changed(&app, room_id, user_id, until)
+}
+
+/// 应用移除群成员消息
+///
+/// # 参数
+/// - `data`: base64 编码的 MessageChatroomMemberDelete
+#[tauri::command]
+pub fn apply_chatroom_member_delete(
+    state: State<RoomMembers>,
+    app: tauri::AppHandle,
+    data: String,
+) -> AppResult<()> {
+    let delete: MessageChatroomMemberDelete = decode_base64_message(&data)?;
+    let room_id = delete.chatroom_id;
+    state.apply_member_delete(delete)?;
+    emit_members_changed(&app, room_id)
+}
+
+/// 获取群成员列表
+///
+/// # 参数
+/// - `room_id`: 群 id
+///
+/// # 返回值
+/// - `Ok(Vec<RoomMember>)`: 成员列表，管理员在前
+#[tauri::command]
+pub fn get_room_members(state: State<RoomMembers>, room_id: u64) -> AppResult<Vec<RoomMember>> {
+    state.members(room_id)
+}
+
+/// 查询成员在群内是否被禁言
+///
+/// 前端据此决定是否禁用输入框
+///
+/// # 参数
+/// - `room_id`: 群 id
+/// - `user_id`: 成员 id（通常是当前登录用户）
+#[tauri::command]
+pub fn is_muted_in_room(state: State<RoomMembers>, room_id: u64, user_id: u64) -> AppResult<bool> {
+    state.is_muted(room_id, user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOM: u64 = 1;
+    const ADMIN: u64 = 10;
+    const MEMBER: u64 = 20;
+
+    /// 创建一个包含一名管理员和一名普通成员的群
+    fn room_with_members() -> RoomMembers {
+        let members = RoomMembers::new();
+        for (member_id, level) in [(ADMIN, 1), (MEMBER, 0)] {
+            members
+                .apply_member_update(MemberUpdate {
+                    chatroom_id: ROOM,
+                    member_id,
+                    level,
+                    created_at: member_id as i64,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        members
+    }
+
+    fn ban(user_id: u64, is_all: bool, duration: i64) -> MessageMemberBan {
+        MessageMemberBan {
+            is_all,
+            user_id,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    fn member_ids(members: &RoomMembers) -> Vec<u64> {
+        members
+            .members(ROOM)
+            .unwrap()
+            .iter()
+            .map(|m| m.member_id)
+            .collect()
+    }
+
+    #[test]
+    fn lists_admins_first_and_applies_member_changes() {
+        let members = room_with_members();
+        assert_eq!(member_ids(&members), vec![ADMIN, MEMBER]);
+
+        members
+            .apply_member_change(
+                ROOM,
+                MessageMemberChange {
+                    user_ids: vec![30],
+                    is_delete: false,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(member_ids(&members), vec![ADMIN, 30, MEMBER]);
+
+        members
+            .apply_level_change(
+                ROOM,
+                MessageMemberLevelChange {
+                    user_id: MEMBER,
+                    level: 2,
+                },
+            )
+            .unwrap();
+        assert_eq!(member_ids(&members), vec![MEMBER, ADMIN, 30]);
+
+        members
+            .apply_member_change(
+                ROOM,
+                MessageMemberChange {
+                    user_ids: vec![30],
+                    is_delete: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(member_ids(&members), vec![MEMBER, ADMIN]);
+    }
+
+    #[test]
+    fn member_delete_removes_members_and_ignores_unknown_rooms() {
+        let members = room_with_members();
+        members
+            .apply_member_delete(MessageChatroomMemberDelete {
+                chatroom_id: ROOM,
+                user_ids: vec![MEMBER, 99],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(member_ids(&members), vec![ADMIN]);
+
+        members
+            .apply_member_delete(MessageChatroomMemberDelete {
+                chatroom_id: 2,
+                user_ids: vec![ADMIN],
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(members.members(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn member_ban_mutes_only_that_member() {
+        let members = room_with_members();
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+
+        let until = members
+            .apply_ban(ROOM, &ban(MEMBER, false, 60_000))
+            .unwrap();
+        assert!(until.is_some_and(|until| until > now_millis()));
+        assert!(members.is_muted(ROOM, MEMBER).unwrap());
+        assert!(!members.is_muted(ROOM, ADMIN).unwrap());
+
+        // 单独禁言对管理员同样生效
+        members.apply_ban(ROOM, &ban(ADMIN, false, 60_000)).unwrap();
+        assert!(members.is_muted(ROOM, ADMIN).unwrap());
+
+        // 时长为 0 表示解除禁言
+        assert_eq!(
+            members.apply_ban(ROOM, &ban(MEMBER, false, 0)).unwrap(),
+            None
+        );
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+    }
+
+    #[test]
+    fn mute_all_exempts_admins() {
+        let members = room_with_members();
+        members.apply_ban(ROOM, &ban(0, true, 60_000)).unwrap();
+        assert!(members.is_muted(ROOM, MEMBER).unwrap());
+        // 不在成员列表中的用户按普通成员处理
+        assert!(members.is_muted(ROOM, 99).unwrap());
+        assert!(!members.is_muted(ROOM, ADMIN).unwrap());
+        assert!(!members.is_muted(2, MEMBER).unwrap());
+
+        members.apply_ban(ROOM, &ban(0, true, 0)).unwrap();
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+    }
+
+    #[test]
+    fn past_ban_is_not_muted_before_expiry_runs() {
+        let members = room_with_members();
+        members.apply_ban(ROOM, &ban(0, true, 60_000)).unwrap();
+        members
+            .rooms
+            .lock()
+            .unwrap()
+            .get_mut(&ROOM)
+            .unwrap()
+            .mute_all_until = Some(now_millis() - 1);
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+    }
+
+    #[test]
+    fn expire_ban_clears_matching_ban() {
+        let members = room_with_members();
+        let until = members
+            .apply_ban(ROOM, &ban(MEMBER, false, 60_000))
+            .unwrap()
+            .unwrap();
+        assert!(members.expire_ban(ROOM, Some(MEMBER), until).unwrap());
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+        // 已经解除的禁言不再重复通知
+        assert!(!members.expire_ban(ROOM, Some(MEMBER), until).unwrap());
+
+        let until = members
+            .apply_ban(ROOM, &ban(0, true, 60_000))
+            .unwrap()
+            .unwrap();
+        assert!(members.expire_ban(ROOM, None, until).unwrap());
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+    }
+
+    #[test]
+    fn expire_ban_keeps_extended_ban() {
+        let members = room_with_members();
+        let first = members
+            .apply_ban(ROOM, &ban(MEMBER, false, 60_000))
+            .unwrap()
+            .unwrap();
+        let extended = members
+            .apply_ban(ROOM, &ban(MEMBER, false, 120_000))
+            .unwrap()
+            .unwrap();
+        assert_ne!(first, extended);
+
+        // 旧定时器到期时禁言已经延长，不能解除
+        assert!(!members.expire_ban(ROOM, Some(MEMBER), first).unwrap());
+        assert!(members.is_muted(ROOM, MEMBER).unwrap());
+        assert!(members.expire_ban(ROOM, Some(MEMBER), extended).unwrap());
+        assert!(!members.is_muted(ROOM, MEMBER).unwrap());
+
+        let first = members
+            .apply_ban(ROOM, &ban(0, true, 60_000))
+            .unwrap()
+            .unwrap();
+        members.apply_ban(ROOM, &ban(0, true, 120_000)).unwrap();
+        assert!(!members.expire_ban(ROOM, None, first).unwrap());
+        assert!(members.is_muted(ROOM, MEMBER).unwrap());
+    }
+
+    #[test]
+    fn expire_ban_ignores_removed_members_and_unknown_rooms() {
+        let members = room_with_members();
+        let until = members
+            .apply_ban(ROOM, &ban(MEMBER, false, 60_000))
+            .unwrap()
+            .unwrap();
+        members
+            .apply_member_delete(MessageChatroomMemberDelete {
+                chatroom_id: ROOM,
+                user_ids: vec![MEMBER],
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!members.expire_ban(ROOM, Some(MEMBER), until).unwrap());
+        assert!(!members.expire_ban(2, None, until).unwrap());
+    }
+}
@@ -0,0 +1,583 @@
+/// 应用设置模块
+///
+/// 设置保存在应用数据目录的 settings.json 中，带有版本号：
+/// - 加载时按版本逐步迁移到当前版本，缺少的字段使用默认值
+/// - 来自更新版本的设置文件只读取不写回，避免降级运行时丢掉新版本的字段，本次运行中的修改不保存
+/// - 修改前校验，校验失败时不保存，返回 `AppError::InvalidSettings`
+/// - 修改后向所有窗口发送 "settings-changed" 事件，并直接通知相关的 Rust 子系统
+///   （开机自启、全局快捷键），通知、免打扰和关闭行为在使用时读取当前设置
+/// - 开机自启的实际状态由系统保存，启动时以系统中的状态为准更新设置
+///
+/// 前端通过 `patch_settings` 以 JSON Merge Patch（RFC 7386）的方式修改设置，
+/// 字段设置为 null 时恢复默认值
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_global_shortcut::Shortcut;
+
+use crate::error::AppError;
+use crate::shortcuts::{self, ShortcutAction};
+
+/// 设置存储文件名
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 版本 0 的关闭行为设置文件，由 lifecycle 模块单独保存，迁移后删除
+const LEGACY_CLOSE_BEHAVIOR_FILE: &str = "close_behavior.json";
+
+/// 当前设置版本
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// 支持的界面语言
+const SUPPORTED_LANGUAGES: &[&str] = &["zh-CN", "en-US"];
+
+/// 主窗口关闭按钮的行为
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    /// 隐藏到系统托盘
+    #[default]
+    Hide,
+    /// 最小化窗口
+    Minimize,
+    /// 退出应用
+    Quit,
+}
+
+/// 新消息通知设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// 是否弹出新消息通知
+    pub enabled: bool,
+    /// 通知中是否显示消息内容，关闭时只显示“收到一条新消息”
+    pub show_preview: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            show_preview: true,
+        }
+    }
+}
+
+/// 免打扰时段，时段内不弹新消息通知
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DoNotDisturb {
+    /// 是否开启免打扰
+    pub enabled: bool,
+    /// 开始时间（本地时间 HH:MM）
+    pub start: String,
+    /// 结束时间（本地时间 HH:MM），早于开始时间表示跨过午夜
+    pub end: String,
+}
+
+impl Default for DoNotDisturb {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+        }
+    }
+}
+
+impl DoNotDisturb {
+    /// 当前本地时间是否处于免打扰时段
+    pub fn is_active_now(&self) -> bool {
+        use chrono::Timelike;
+
+        let now = chrono::Local::now().time();
+        self.is_active_at(now.hour() * 60 + now.minute())
+    }
+
+    /// 指定时间（一天中的第几分钟）是否处于免打扰时段
+    fn is_active_at(&self, minute: u32) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if !self.enabled {
+            false
+        } else if start < end {
+            start <= minute && minute < end
+        } else {
+            minute >= start || minute < end
+        }
+    }
+}
+
+/// 解析 HH:MM 格式的时间，返回一天中的第几分钟
+fn parse_time(time: &str) -> Option<u32> {
+    let (hour, minute) = time.split_once(':')?;
+    if hour.len() != 2 || minute.len() != 2 {
+        return None;
+    }
+    let hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+/// 全局快捷键设置，组合键如 "CmdOrCtrl+Alt+W"，为空字符串时不注册
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    /// 显示/隐藏主窗口
+    pub toggle_main_window: String,
+    /// 清除未读数
+    pub clear_unread: String,
+    /// 截图
+    pub screenshot: String,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            toggle_main_window: "CmdOrCtrl+Alt+W".to_string(),
+            clear_unread: "CmdOrCtrl+Alt+R".to_string(),
+            screenshot: "CmdOrCtrl+Alt+A".to_string(),
+        }
+    }
+}
+
+impl Shortcuts {
+    /// 获取动作对应的组合键
+    pub fn get(&self, action: ShortcutAction) -> &str {
+        match action {
+            ShortcutAction::ToggleMainWindow => &self.toggle_main_window,
+            ShortcutAction::ClearUnread => &self.clear_unread,
+            ShortcutAction::Screenshot => &self.screenshot,
+        }
+    }
+}
+
+/// 应用设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 设置版本
+    pub version: u32,
+    /// 新消息通知
+    pub notifications: NotificationSettings,
+    /// 免打扰时段
+    pub do_not_disturb: DoNotDisturb,
+    /// 主窗口关闭按钮的行为
+    pub close_behavior: CloseBehavior,
+    /// 是否已经显示过托盘说明
+    pub tray_hint_shown: bool,
+    /// 全局快捷键
+    pub shortcuts: Shortcuts,
+    /// 是否开机自启
+    pub autostart: bool,
+    /// 界面语言
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            notifications: NotificationSettings::default(),
+            do_not_disturb: DoNotDisturb::default(),
+            close_behavior: CloseBehavior::default(),
+            tray_hint_shown: false,
+            shortcuts: Shortcuts::default(),
+            autostart: false,
+            language: "zh-CN".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// 校验设置
+    pub fn validate(&self) -> Result<(), AppError> {
+        let dnd = &self.do_not_disturb;
+        let (Some(start), Some(end)) = (parse_time(&dnd.start), parse_time(&dnd.end)) else {
+            return Err(AppError::InvalidSettings(format!(
+                "do_not_disturb: invalid time {} - {}, expected HH:MM",
+                dnd.start, dnd.end
+            )));
+        };
+        if start == end {
+            return Err(AppError::InvalidSettings(
+                "do_not_disturb: start and end must differ".to_string(),
+            ));
+        }
+
+        // 组合键必须有效，且不能有两个动作使用同一个组合键
+        let mut used: Vec<(ShortcutAction, Shortcut)> = Vec::new();
+        for action in ShortcutAction::ALL {
+            let accelerator = self.shortcuts.get(action);
+            if accelerator.is_empty() {
+                continue;
+            }
+            let shortcut: Shortcut = accelerator.parse().map_err(|e| {
+                AppError::InvalidSettings(format!("shortcuts.{}: {}", action.key(), e))
+            })?;
+            if let Some((other, _)) = used.iter().find(|(_, used)| *used == shortcut) {
+                return Err(AppError::InvalidSettings(format!(
+                    "shortcuts.{}: {} is already used by shortcuts.{}",
+                    action.key(),
+                    accelerator,
+                    other.key()
+                )));
+            }
+            used.push((action, shortcut));
+        }
+
+        if !SUPPORTED_LANGUAGES.contains(&self.language.as_str()) {
+            return Err(AppError::InvalidSettings(format!(
+                "language: unsupported language {}",
+                self.language
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 设置变化事件
+#[derive(Debug, Clone, Serialize)]
+struct SettingsChanged {
+    /// 修改后的设置
+    settings: Settings,
+    /// 发生变化的顶层字段
+    changed: Vec<String>,
+}
+
+/// 把旧版本的设置逐步迁移到当前版本
+///
+/// 每个版本一步，新增版本时在末尾追加
+fn migrate(mut value: Value) -> Value {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    // 0 -> 1：关闭行为从 close_behavior.json 合并进来，behavior 重命名为 close_behavior
+    if version < 1 {
+        if let Some(object) = value.as_object_mut() {
+            if let Some(behavior) = object.remove("behavior") {
+                object.insert("close_behavior".to_string(), behavior);
+            }
+        }
+    }
+
+    // 1 -> 2：快捷键从 Option 改为字符串，用户没有设置（null）的快捷键保持不注册，迁移为空字符串；
+    // 版本 1 中还没有的快捷键使用默认组合键
+    if version < 2 {
+        if let Some(Value::Object(shortcuts)) = value.get_mut("shortcuts") {
+            for shortcut in shortcuts.values_mut().filter(|shortcut| shortcut.is_null()) {
+                *shortcut = Value::String(String::new());
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), SETTINGS_VERSION.into());
+    }
+    value
+}
+
+/// 解析设置文件内容，迁移到当前版本并校验
+///
+/// # 返回值
+/// - `Ok((设置, 文件中的版本号))`
+fn load(json: &str) -> Result<(Settings, u64), AppError> {
+    let raw: Value =
+        serde_json::from_str(json).map_err(|e| AppError::InvalidSettings(e.to_string()))?;
+    let version = raw.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > SETTINGS_VERSION as u64 {
+        tracing::warn!(version, "设置文件来自更新的版本，按当前版本读取，不写回");
+    }
+    let settings: Settings = serde_json::from_value(migrate(raw))
+        .map_err(|e| AppError::InvalidSettings(e.to_string()))?;
+    settings.validate()?;
+    Ok((settings, version))
+}
+
+/// 按 JSON Merge Patch 合并，patch 中为 null 的字段从 target 中删除
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// 设置存储
+pub struct SettingsStore {
+    /// 存储文件路径
+    path: PathBuf,
+    /// 当前设置
+    settings: Mutex<Settings>,
+    /// 设置文件来自更新的版本，不写回磁盘
+    read_only: bool,
+}
+
+impl SettingsStore {
+    /// 创建设置存储并从磁盘加载，必要时迁移旧版本的设置
+    ///
+    /// 无法解析或校验失败的设置文件会备份为 settings.json.bak，然后使用默认设置；
+    /// 来自更新版本的设置文件保持原样
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于获取应用数据目录
+    pub fn new(app: &tauri::AppHandle) -> Result<Self, AppError> {
+        let dir = app.path().app_data_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(SETTINGS_FILE);
+        let legacy_path = dir.join(LEGACY_CLOSE_BEHAVIOR_FILE);
+
+        let source = if path.exists() { &path } else { &legacy_path };
+        let (settings, version) = match std::fs::read_to_string(source) {
+            Ok(json) => match load(&json) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    tracing::warn!(error = %e, "设置文件无效，使用默认设置");
+                    let _ = std::fs::rename(source, path.with_extension("json.bak"));
+                    (Settings::default(), 0)
+                }
+            },
+            Err(_) => (Settings::default(), 0),
+        };
+
+        let store = Self {
+            path,
+            settings: Mutex::new(settings.clone()),
+            read_only: version > SETTINGS_VERSION as u64,
+        };
+        if store.read_only {
+            return Ok(store);
+        }
+        store.save(&settings)?;
+        if legacy_path.exists() {
+            tracing::info!("关闭行为设置已迁移到 settings.json");
+            let _ = std::fs::remove_file(legacy_path);
+        }
+        Ok(store)
+    }
+
+    /// 获取当前设置
+    pub fn get(&self) -> Result<Settings, AppError> {
+        Ok(self.settings.lock()?.clone())
+    }
+
+    /// 写入存储文件，设置文件来自更新的版本时跳过
+    fn save(&self, settings: &Settings) -> Result<(), AppError> {
+        if self.read_only {
+            tracing::warn!("设置文件来自更新的版本，修改只在本次运行中生效");
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(settings)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        std::fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    /// 根据当前设置生成新设置，校验通过后保存
+    ///
+    /// # 返回值
+    /// - `Ok((修改前, 修改后))`
+    fn replace(
+        &self,
+        f: impl FnOnce(&Settings) -> Result<Settings, AppError>,
+    ) -> Result<(Settings, Settings), AppError> {
+        let mut current = self.settings.lock()?;
+        let mut next = f(&current)?;
+        next.version = SETTINGS_VERSION;
+        next.validate()?;
+        if next != *current {
+            self.save(&next)?;
+        }
+        let previous = std::mem::replace(&mut *current, next.clone());
+        Ok((previous, next))
+    }
+}
+
+/// 获取当前设置，设置尚未加载时返回默认设置
+pub fn current(app: &tauri::AppHandle) -> Settings {
+    app.try_state::<SettingsStore>()
+        .and_then(|store| store.get().ok())
+        .unwrap_or_default()
+}
+
+/// 在 Rust 中修改设置，与前端修改一样发送事件并通知子系统
+pub fn update(app: &tauri::AppHandle, f: impl FnOnce(&mut Settings)) -> Result<Settings, AppError> {
+    change(app, |current| {
+        let mut next = current.clone();
+        f(&mut next);
+        Ok(next)
+    })
+}
+
+/// 根据当前设置生成新设置，保存后发送事件并通知子系统
+///
+/// 开机自启以系统中的状态为准，保存前先修改系统：修改失败时返回错误，设置保持不变；
+/// 系统已经修改但保存失败时恢复系统原来的状态
+fn change(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&Settings) -> Result<Settings, AppError>,
+) -> Result<Settings, AppError> {
+    let mut applied_autostart = None;
+    let result = app.state::<SettingsStore>().replace(|current| {
+        let next = f(current)?;
+        next.validate()?;
+        if next.autostart != current.autostart {
+            apply_autostart(app, next.autostart)?;
+            applied_autostart = Some(next.autostart);
+        }
+        Ok(next)
+    });
+    let (previous, settings) = match result {
+        Ok(changed) => changed,
+        Err(e) => {
+            if let Some(enabled) = applied_autostart {
+                if let Err(e) = apply_autostart(app, !enabled) {
+                    tracing::warn!(error = %e, "恢复开机自启状态失败");
+                }
+            }
+            return Err(e);
+        }
+    };
+    on_changed(app, &previous, &settings);
+    Ok(settings)
+}
+
+/// 设置变化后发送事件并通知子系统
+fn on_changed(app: &tauri::AppHandle, previous: &Settings, settings: &Settings) {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) = (
+        serde_json::to_value(previous),
+        serde_json::to_value(settings),
+    ) else {
+        return;
+    };
+    let changed: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+    tracing::info!(changed = ?changed, "设置已修改");
+
+    if previous.shortcuts != settings.shortcuts {
+        shortcuts::apply(app, &settings.shortcuts);
+    }
+
+    let payload = SettingsChanged {
+        settings: settings.clone(),
+        changed,
+    };
+    let _ = app.emit("settings-changed", payload);
+}
+
+/// 开启或关闭开机自启
+fn apply_autostart(app: &tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| {
+        tracing::warn!(error = %e, enabled, "修改开机自启失败");
+        AppError::Autostart(e.to_string())
+    })
+}
+
+/// 应用启动时按设置注册全局快捷键，并同步开机自启的实际状态
+///
+/// 开机自启由系统保存，可能在应用之外被修改（如系统设置中关闭），
+/// 启动时以系统中的状态为准更新设置，不再反过来修改系统
+pub fn init(app: &tauri::AppHandle) {
+    shortcuts::apply(app, &current(app).shortcuts);
+    sync_autostart(app);
+}
+
+/// 读取开机自启的实际状态并写入设置
+fn sync_autostart(app: &tauri::AppHandle) {
+    let enabled = match app.autolaunch().is_enabled() {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            tracing::warn!(error = %e, "读取开机自启状态失败");
+            return;
+        }
+    };
+    let result = app.state::<SettingsStore>().replace(|current| {
+        let mut next = current.clone();
+        next.autostart = enabled;
+        Ok(next)
+    });
+    match result {
+        Ok((previous, _)) if previous.autostart != enabled => {
+            tracing::info!(enabled, "开机自启设置已与系统同步");
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "同步开机自启设置失败"),
+    }
+}
+
+/// 获取设置
+#[tauri::command]
+pub fn get_settings(state: State<SettingsStore>) -> Result<Settings, AppError> {
+    state.get()
+}
+
+/// 修改设置
+///
+/// # 参数
+/// - `patch`: JSON Merge Patch，如 `{"do_not_disturb": {"enabled": true}}`，字段为 null 时恢复默认值
+///
+/// # 返回值
+/// - `Ok(Settings)`: 修改后的设置
+/// - `Err(AppError::InvalidSettings)`: 修改后的设置无效，设置保持不变
+/// - `Err(AppError::Autostart)`: 修改系统的开机自启失败，设置保持不变
+#[tauri::command]
+pub fn patch_settings(app: tauri::AppHandle, patch: Value) -> Result<Settings, AppError> {
+    change(&app, |current| {
+        let mut value =
+            serde_json::to_value(current).map_err(|e| AppError::Internal(e.to_string()))?;
+        merge_patch(&mut value, &patch);
+        serde_json::from_value(value).map_err(|e| AppError::InvalidSettings(e.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_unset_shortcuts_to_disabled() {
+        let (settings, version) = load(
+            r#"{"version": 1, "shortcuts": {"toggle_main_window": null}, "close_behavior": "quit"}"#,
+        )
+        .unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.close_behavior, CloseBehavior::Quit);
+        assert_eq!(settings.shortcuts.toggle_main_window, "");
+        assert_eq!(
+            settings.shortcuts.clear_unread,
+            Shortcuts::default().clear_unread
+        );
+
+        let (settings, _) =
+            load(r#"{"version": 1, "shortcuts": {"toggle_main_window": "CmdOrCtrl+Shift+D"}}"#)
+                .unwrap();
+        assert_eq!(settings.shortcuts.toggle_main_window, "CmdOrCtrl+Shift+D");
+    }
+}
@@ -0,0 +1,217 @@
+/// 全局快捷键模块
+///
+/// 应用内置以下全局快捷键，组合键保存在设置的 `shortcuts` 中，可以在设置中修改或关闭：
+/// - `toggle_main_window`: 显示/隐藏主窗口
+/// - `clear_unread`: 清除未读数
+/// - `screenshot`: 截图，应用没有内置截图功能，只通知前端处理
+///
+/// 每次触发都会向前端发送 "global-shortcut-triggered" 事件。
+/// 启动时和快捷键设置修改后重新注册全部快捷键，每个快捷键的注册结果保存在状态中，
+/// 组合键无效、已被应用内其他功能（如前端通过插件注册的快捷键）或其他应用占用时
+/// 记录失败原因，并向前端发送 "shortcut-status-changed" 事件
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::error::AppError;
+use crate::settings::Shortcuts;
+use crate::tray;
+use crate::unread_count::UnreadCount;
+use crate::window;
+
+/// 全局快捷键对应的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// 显示/隐藏主窗口
+    ToggleMainWindow,
+    /// 清除未读数
+    ClearUnread,
+    /// 截图
+    Screenshot,
+}
+
+impl ShortcutAction {
+    /// 所有动作
+    pub const ALL: [ShortcutAction; 3] = [
+        ShortcutAction::ToggleMainWindow,
+        ShortcutAction::ClearUnread,
+        ShortcutAction::Screenshot,
+    ];
+
+    /// 动作在设置中的字段名
+    pub fn key(self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleMainWindow => "toggle_main_window",
+            ShortcutAction::ClearUnread => "clear_unread",
+            ShortcutAction::Screenshot => "screenshot",
+        }
+    }
+}
+
+/// 单个快捷键的注册结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutStatus {
+    /// 动作
+    pub action: ShortcutAction,
+    /// 设置中的组合键，为空表示已关闭
+    pub shortcut: String,
+    /// 是否注册成功
+    pub registered: bool,
+    /// 注册失败的原因
+    pub error: Option<String>,
+}
+
+/// 快捷键触发事件
+#[derive(Debug, Clone, Serialize)]
+struct ShortcutTriggered {
+    action: ShortcutAction,
+}
+
+/// 全局快捷键状态
+#[derive(Default)]
+pub struct GlobalShortcuts {
+    /// 本模块注册的快捷键，重新注册前先注销
+    registered: Mutex<Vec<Shortcut>>,
+    /// 每个动作最近一次的注册结果
+    status: Mutex<Vec<ShortcutStatus>>,
+}
+
+impl GlobalShortcuts {
+    /// 创建全局快捷键状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取每个快捷键的注册结果
+    pub fn status(&self) -> Result<Vec<ShortcutStatus>, AppError> {
+        Ok(self.status.lock()?.clone())
+    }
+}
+
+/// 执行快捷键对应的动作
+fn trigger(app: &tauri::AppHandle, action: ShortcutAction) {
+    tracing::debug!(action = action.key(), "触发全局快捷键");
+    match action {
+        ShortcutAction::ToggleMainWindow => window::toggle_main_window(app),
+        ShortcutAction::ClearUnread => {
+            if let Ok(count) = app.state::<UnreadCount>().clear() {
+                tray::update_tray_title(app, count);
+                let _ = crate::utils::emit_unread_count_changed(app, count);
+            }
+        }
+        // 截图由前端处理
+        ShortcutAction::Screenshot => {}
+    }
+    let _ = app.emit("global-shortcut-triggered", ShortcutTriggered { action });
+}
+
+/// 注册单个快捷键
+///
+/// # 返回值
+/// - `Ok(Shortcut)`: 注册成功的快捷键
+/// - `Err(String)`: 注册失败的原因
+fn register(
+    app: &tauri::AppHandle,
+    action: ShortcutAction,
+    accelerator: &str,
+) -> Result<Shortcut, String> {
+    let shortcut: Shortcut = accelerator
+        .parse()
+        .map_err(|e| format!("无效的快捷键: {}", e))?;
+
+    let global_shortcut = app.global_shortcut();
+    // 本模块的快捷键已全部注销，仍处于注册状态说明被应用内其他功能占用
+    if global_shortcut.is_registered(shortcut) {
+        return Err("快捷键已被应用内其他功能占用".to_string());
+    }
+    global_shortcut
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                trigger(app, action);
+            }
+        })
+        .map_err(|e| format!("快捷键注册失败，可能已被其他应用占用: {}", e))?;
+    Ok(shortcut)
+}
+
+/// 按设置重新注册全部快捷键
+///
+/// 在启动时和快捷键设置修改后调用，注册失败不影响其他快捷键
+pub fn apply(app: &tauri::AppHandle, config: &Shortcuts) {
+    let Some(state) = app.try_state::<GlobalShortcuts>() else {
+        return;
+    };
+    // 注册期间一直持有锁，避免同时修改设置时交错注册
+    let Ok(mut registered) = state.registered.lock() else {
+        return;
+    };
+
+    let global_shortcut = app.global_shortcut();
+    for shortcut in registered.drain(..) {
+        if let Err(e) = global_shortcut.unregister(shortcut) {
+            tracing::warn!(error = %e, "注销全局快捷键失败");
+        }
+    }
+
+    let mut status = Vec::with_capacity(ShortcutAction::ALL.len());
+    for action in ShortcutAction::ALL {
+        let accelerator = config.get(action);
+        let result = if accelerator.is_empty() {
+            Ok(None)
+        } else {
+            register(app, action, accelerator).map(Some)
+        };
+        let error = match result {
+            Ok(Some(shortcut)) => {
+                registered.push(shortcut);
+                None
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(action = action.key(), shortcut = accelerator, error = %e, "注册全局快捷键失败");
+                Some(e)
+            }
+        };
+        status.push(ShortcutStatus {
+            action,
+            shortcut: accelerator.to_string(),
+            registered: !accelerator.is_empty() && error.is_none(),
+            error,
+        });
+    }
+    drop(registered);
+
+    if let Ok(mut current) = state.status.lock() {
+        *current = status.clone();
+    }
+    let _ = app.emit("shortcut-status-changed", status);
+}
+
+/// 获取每个全局快捷键的注册结果
+///
+/// 注册成功的快捷键可能之后被前端通过插件注销，返回前重新检查是否仍处于注册状态
+///
+/// # 返回值
+/// - `Ok(Vec<ShortcutStatus>)`: 按动作排列的注册结果，注册失败时包含失败原因
+#[tauri::command]
+pub fn get_shortcut_status(
+    app: tauri::AppHandle,
+    state: State<GlobalShortcuts>,
+) -> Result<Vec<ShortcutStatus>, AppError> {
+    let global_shortcut = app.global_shortcut();
+    let mut status = state.status()?;
+    for item in status.iter_mut().filter(|item| item.registered) {
+        let still_registered = item
+            .shortcut
+            .parse::<Shortcut>()
+            .is_ok_and(|shortcut| global_shortcut.is_registered(shortcut));
+        if !still_registered {
+            item.registered = false;
+            item.error = Some("快捷键已被应用内其他功能注销，修改快捷键设置后重新注册".to_string());
+        }
+    }
+    Ok(status)
+}
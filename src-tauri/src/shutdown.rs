@@ -0,0 +1,154 @@
+/// 后台任务停止协调模块
+///
+/// 各子系统的后台任务（提醒调度、定时器、头像和媒体下载、语音播报等）在启动时登记，
+/// 退出流程（lifecycle::quit，由托盘“退出”或 RunEvent::ExitRequested 触发）调用
+/// `Shutdown::shutdown`：
+/// 1. 发出取消信号，`spawn` 启动的异步任务在下一个 await 点结束，线程自行检查取消信号
+/// 2. 等待所有登记的任务结束，最多等待 `SHUTDOWN_TIMEOUT`
+/// 3. 超时仍未结束的任务记录到日志
+///
+/// 任务只在 await 点被取消，同步的写文件操作不会被打断
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::Manager;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// 等待后台任务结束的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 停止协调状态
+#[derive(Default)]
+struct ShutdownInner {
+    /// 取消信号
+    token: CancellationToken,
+    /// 运行中的任务，按登记 id 索引任务名
+    tasks: Mutex<HashMap<u64, &'static str>>,
+    /// 下一个登记 id
+    next_id: AtomicU64,
+    /// 有任务结束时通知
+    task_finished: Notify,
+}
+
+/// 后台任务停止协调器
+///
+/// 在 `run()` 中创建并通过 `.manage` 注册，克隆后共享同一份状态
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<ShutdownInner>,
+}
+
+/// 任务登记凭证，销毁时从运行中的任务里移除
+pub struct TaskGuard {
+    shutdown: Shutdown,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Ok(mut tasks) = self.shutdown.inner.tasks.lock() {
+            tasks.remove(&self.id);
+        }
+        self.shutdown.inner.task_finished.notify_waiters();
+    }
+}
+
+impl Shutdown {
+    /// 创建停止协调器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取消信号，线程等无法使用 `spawn` 的任务自行检查
+    pub fn token(&self) -> CancellationToken {
+        self.inner.token.clone()
+    }
+
+    /// 是否已经发出取消信号
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.token.is_cancelled()
+    }
+
+    /// 登记一个任务，任务结束时销毁返回的凭证
+    ///
+    /// # 参数
+    /// - `name`: 任务名，超时未结束时写入日志
+    pub fn register(&self, name: &'static str) -> TaskGuard {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut tasks) = self.inner.tasks.lock() {
+            tasks.insert(id, name);
+        }
+        TaskGuard {
+            shutdown: self.clone(),
+            id,
+        }
+    }
+
+    /// 运行中的任务名
+    fn running(&self) -> Vec<&'static str> {
+        self.inner
+            .tasks
+            .lock()
+            .map(|tasks| tasks.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// 发出取消信号并等待所有任务结束
+    ///
+    /// 重复调用时再次等待仍在运行的任务
+    pub async fn shutdown(&self) {
+        let running = self.running();
+        tracing::info!(tasks = running.len(), "停止后台任务");
+        self.inner.token.cancel();
+
+        let all_finished = async {
+            loop {
+                // 先注册通知再检查，避免错过检查之后、等待之前结束的任务
+                let finished = self.inner.task_finished.notified();
+                if self.running().is_empty() {
+                    break;
+                }
+                finished.await;
+            }
+        };
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, all_finished)
+            .await
+            .is_err()
+        {
+            let mut remaining = self.running();
+            remaining.sort_unstable();
+            tracing::warn!(tasks = ?remaining, "后台任务未能在超时前停止");
+        } else {
+            tracing::info!("后台任务已全部停止");
+        }
+    }
+}
+
+/// 启动一个登记到停止协调器的异步任务，收到取消信号时在下一个 await 点结束
+///
+/// # 参数
+/// - `name`: 任务名
+/// - `task`: 任务
+pub fn spawn<F>(app: &tauri::AppHandle, name: &'static str, task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let shutdown = app.state::<Shutdown>().inner().clone();
+    if shutdown.is_cancelled() {
+        tracing::debug!(task = name, "应用正在退出，不再启动后台任务");
+        return;
+    }
+    let guard = shutdown.register(name);
+    let token = shutdown.token();
+    tauri::async_runtime::spawn(async move {
+        let _guard = guard;
+        tokio::select! {
+            _ = task => {}
+            _ = token.cancelled() => tracing::debug!(task = name, "后台任务已取消"),
+        }
+    });
+}
@@ -1,38 +1,75 @@
+use std::sync::Mutex;
+
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     Manager, Runtime,
 };
 
-use crate::{dock, unread_count::UnreadCount, window};
+use crate::{dock, error::AppError, unread_count::UnreadCount, window};
+
+/// 主托盘图标的 ID
+const TRAY_ID: &str = "main-tray";
 
 /// 系统托盘管理模块
 ///
 /// 负责创建托盘图标、菜单，处理托盘事件，更新托盘显示等功能
 
+/// 托盘状态
+///
+/// 记录当前的提醒高亮内容。未读数变化时托盘仍然显示提醒，直到提醒被关闭或稍后提醒
+#[derive(Debug, Default)]
+pub struct TrayState {
+    /// 提醒高亮的内容，None 表示没有提醒，托盘显示未读数
+    attention: Mutex<Option<String>>,
+}
+
+impl TrayState {
+    /// 创建托盘状态
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前的提醒高亮内容
+    fn attention(&self) -> Option<String> {
+        self.attention
+            .lock()
+            .ok()
+            .and_then(|attention| attention.clone())
+    }
+}
+
 /// 更新托盘标题、提示和相关 UI 元素
 ///
+/// 只是未读数的展示，尽力更新：托盘不存在或设置失败时记录警告，
+/// 不影响窗口标题和 Dock 徽章的更新，也不让已经修改了未读数的调用方失败。
+/// 有提醒高亮时托盘显示提醒内容，窗口标题和 Dock 徽章仍然显示未读数
+///
 /// # 参数
 /// - `app`: Tauri 应用句柄引用
 /// - `count`: 未读消息数量
-///
-/// # 返回值
-/// - `Ok(())`: 操作成功
-/// - `Err(String)`: 操作失败，包含错误信息
-pub fn update_tray_title(app: &tauri::AppHandle, count: u32) -> Result<(), String> {
+pub fn update_tray_title(app: &tauri::AppHandle, count: u32) {
     // 更新托盘提示和标题
     // app.tray_by_id 尝试获取指定 ID 的托盘图标
     // if let Some(tray) = ... 是模式匹配，当找到托盘时执行大括号中的代码
-    if let Some(tray) = app.tray_by_id("main-tray") {
-        // 根据未读数量创建不同的标题文本
-        let title = if count > 0 {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let attention = app
+            .try_state::<TrayState>()
+            .and_then(|state| state.attention());
+
+        // 根据提醒和未读数量创建不同的标题文本
+        let title = if attention.is_some() {
+            "Demo ⏰".to_string() // 有待处理的提醒
+        } else if count > 0 {
             format!("Demo {}", count) // 在标题中显示未读数
         } else {
             "Demo".to_string() // 没有未读消息时显示普通标题
         };
 
-        // 根据未读数量创建不同的提示文本
-        let tooltip = if count > 0 {
+        // 根据提醒和未读数量创建不同的提示文本
+        let tooltip = if let Some(message) = attention {
+            format!("⏰ Demo - {}", message)
+        } else if count > 0 {
             format!("🔴 Demo - {} 条未读消息", count)
         } else {
             "✅ Demo - 没有未读消息".to_string()
@@ -40,22 +77,37 @@ pub fn update_tray_title(app: &tauri::AppHandle, count: u32) -> Result<(), Strin
 
         // 设置托盘标题（在某些平台可见）
         // Some(&title) 创建 Option 类型，包含 title 的引用
-        // map_err 将可能的错误转换为字符串
-        // ? 操作符：如果操作失败，立即返回错误
-        tray.set_title(Some(&title)).map_err(|e| e.to_string())?;
+        if let Err(e) = tray.set_title(Some(&title)) {
+            tracing::warn!(error = %e, "设置托盘标题失败");
+        }
 
         // 设置鼠标悬停提示
-        tray.set_tooltip(Some(&tooltip))
-            .map_err(|e| e.to_string())?;
+        if let Err(e) = tray.set_tooltip(Some(&tooltip)) {
+            tracing::warn!(error = %e, "设置托盘提示失败");
+        }
+    } else {
+        tracing::warn!(id = TRAY_ID, "托盘图标不存在，跳过更新托盘未读数");
     }
 
     // 更新窗口标题
-    window::update_window_title(app, count)?;
+    window::update_window_title(app, count);
 
     // 在macOS上设置Dock徽章
     dock::set_dock_badge(count);
+}
 
-    // 返回成功结果
+/// 设置或取消托盘的提醒高亮
+///
+/// 有待处理的提醒时在托盘标题和提示中显示提醒内容，之后未读数变化也不会覆盖；
+/// 取消时恢复为未读数显示
+///
+/// # 参数
+/// - `app`: Tauri 应用句柄引用
+/// - `message`: 提醒内容，`None` 表示取消高亮
+pub fn set_tray_attention(app: &tauri::AppHandle, message: Option<&str>) -> Result<(), AppError> {
+    *app.state::<TrayState>().attention.lock()? = message.map(str::to_string);
+    let count = app.state::<UnreadCount>().get()?;
+    update_tray_title(app, count);
     Ok(())
 }
 
@@ -100,7 +152,7 @@ pub fn create_tray_icon(app: &tauri::AppHandle) -> Result<(), tauri::Error> {
 
     // 创建托盘图标
     // TrayIconBuilder 使用构建器模式来设置托盘图标的各种属性
-    let _tray = TrayIconBuilder::with_id("main-tray")
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu) // 设置托盘菜单
         .tooltip("Demo") // 设置鼠标悬停提示
         .title("Demo") // 添加标题，可以在某些平台显示文字
@@ -152,7 +204,7 @@ pub fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent)
             // 尝试增加未读数
             if let Ok(new_count) = state.increment() {
                 // 更新托盘标题等 UI 元素
-                let _ = update_tray_title(app, new_count);
+                update_tray_title(app, new_count);
                 // 使用工具函数发送事件通知前端
                 let _ = crate::utils::emit_unread_count_changed(app, new_count);
             }
@@ -164,14 +216,15 @@ pub fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent)
             // 尝试清除未读数
             if let Ok(_) = state.clear() {
                 // 更新 UI 元素显示无未读消息
-                let _ = update_tray_title(app, 0);
+                update_tray_title(app, 0);
                 // 使用工具函数发出事件通知前端未读数已清零
                 let _ = crate::utils::emit_unread_count_changed(app, 0);
             }
         }
         "quit" => {
             // 处理"退出"菜单项
-            app.exit(0); // 退出应用程序，0 表示正常退出
+            // 经过完整的退出流程，等待前端完成收尾工作并保存状态后再退出
+            crate::lifecycle::quit(app);
         }
         _ => {} // 忽略其它菜单项
     }
@@ -1,5 +1,7 @@
 use std::sync::{Arc, Mutex};
 
+use crate::error::AppError;
+
 /// 全局状态管理未读数
 ///
 /// 这个结构体用于在整个应用程序中管理未读消息的数量
@@ -32,14 +34,13 @@ impl UnreadCount {
     ///
     /// # 返回值
     /// - `Ok(u32)`: 成功获取未读数
-    /// - `Err(String)`: 获取失败，通常是因为互斥锁被毒化（poisoned）
-    pub fn get(&self) -> Result<u32, String> {
+    /// - `Err(AppError::LockPoisoned)`: 获取失败，因为互斥锁被毒化（poisoned）
+    pub fn get(&self) -> Result<u32, AppError> {
         // 尝试获取互斥锁
         // lock() 方法返回 Result<MutexGuard<u32>, PoisonError<MutexGuard<u32>>>
-        self.count
-            .lock() // 获取互斥锁
-            .map(|count| *count) // 解引用获取实际的 u32 值
-            .map_err(|e| e.to_string()) // 将错误转换为 String
+        // ? 操作符会通过 From 把 PoisonError 转换为 AppError::LockPoisoned
+        let count = self.count.lock()?;
+        Ok(*count) // 解引用获取实际的 u32 值
     }
 
     /// 增加未读数
@@ -49,11 +50,11 @@ impl UnreadCount {
     ///
     /// # 返回值
     /// - `Ok(u32)`: 成功增加，返回新的未读数
-    /// - `Err(String)`: 操作失败，通常是因为互斥锁被毒化
-    pub fn increment(&self) -> Result<u32, String> {
+    /// - `Err(AppError::LockPoisoned)`: 操作失败，因为互斥锁被毒化
+    pub fn increment(&self) -> Result<u32, AppError> {
         // 获取互斥锁的可变引用
         // ? 操作符：如果 lock() 失败，立即返回错误
-        let mut count = self.count.lock().map_err(|e| e.to_string())?;
+        let mut count = self.count.lock()?;
 
         // 增加计数器的值
         // *count 解引用 MutexGuard 以获取对 u32 的可变引用
@@ -70,10 +71,10 @@ impl UnreadCount {
     ///
     /// # 返回值
     /// - `Ok(u32)`: 操作成功，返回清零后的数值（总是0）
-    /// - `Err(String)`: 操作失败，包含错误信息
-    pub fn clear(&self) -> Result<u32, String> {
+    /// - `Err(AppError::LockPoisoned)`: 操作失败，因为互斥锁被毒化
+    pub fn clear(&self) -> Result<u32, AppError> {
         // 直接设置为0而不是调用已删除的set方法
-        let mut count = self.count.lock().map_err(|e| e.to_string())?;
+        let mut count = self.count.lock()?;
         *count = 0;
         Ok(*count)
     }
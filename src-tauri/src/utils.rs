@@ -2,12 +2,15 @@
 ///
 /// 包含应用程序中经常使用的通用工具函数和类型别名
 /// 这个模块主要提供事件发送和错误处理的通用功能
+use base64::{engine::general_purpose, Engine as _};
 use tauri::Emitter;
 
+use crate::error::AppError;
+
 /// 应用程序通用错误类型别名
 ///
 /// 使用 String 作为错误类型，便于错误信息的传递和显示
-/// 这样所有的错误都可以统一处理，简化错误处理逻辑
+/// 新代码优先使用 `error::AppError`，前端可以据此区分错误类型
 pub type AppResult<T> = Result<T, String>;
 
 /// 发送未读数变化事件到前端
@@ -29,3 +32,41 @@ pub fn emit_unread_count_changed(app: &tauri::AppHandle, count: u32) -> AppResul
     app.emit("unread-count-changed", count)
         .map_err(|e| e.to_string()) // 将 Tauri 错误转换为字符串
 }
+
+/// 解码前端传入的 base64 编码的 protobuf 消息
+///
+/// 前端通过长连接收到的 protobuf 二进制数据会以 base64 字符串的形式传给 Rust，
+/// 这个函数统一完成 base64 解码和 protobuf 解码两步，错误信息与 commands 模块保持一致
+///
+/// # 参数
+/// - `data`: base64 编码的字节数组
+///
+/// # 返回值
+/// - `Ok(T)`: 解码后的 protobuf 消息
+/// - `Err(AppError)`: base64 或 protobuf 解码失败
+pub fn decode_base64_message<T: prost::Message + Default>(data: &str) -> Result<T, AppError> {
+    let bytes = general_purpose::STANDARD.decode(data)?;
+    Ok(crate::pb::decode_message::<T>(&bytes)?)
+}
+
+/// 获取当前的 Unix 时间戳（毫秒）
+///
+/// 服务端下发的时间字段（如 created_at、禁言时长）都以毫秒为单位，
+/// 这里统一使用毫秒，方便直接比较
+pub fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 把服务端时间统一为毫秒
+///
+/// 公告、提醒等推送的时间字段没有约定单位，小于 10^12 的值按秒处理
+pub fn to_millis(timestamp: i64) -> i64 {
+    if timestamp > 0 && timestamp < 1_000_000_000_000 {
+        timestamp * 1000
+    } else {
+        timestamp
+    }
+}
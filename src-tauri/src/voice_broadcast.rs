@@ -0,0 +1,479 @@
+/// 语音播报模块
+///
+/// 处理 EventCommon 类型 2（语音播报），把播报文字放入队列，
+/// 由后台任务按顺序通过 TTS 后端朗读，朗读完成后向前端发送 "voice-broadcast-played" 事件
+///
+/// 播报任务登记到停止协调器，空闲时随取消信号立即结束；应用退出时丢弃队列中剩余的播报，
+/// 正在朗读的一条读完后退出。朗读会阻塞到结束，放在阻塞线程池中执行
+///
+/// TTS 后端通过 `TtsBackend` trait 抽象：
+/// - Linux 上优先使用 speech-dispatcher（spd-say），其次是 espeak
+/// - 没有可用的语音合成程序或其他平台使用 `NullBackend`，只记录日志不发声
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, State};
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use tokio_util::sync::CancellationToken;
+
+use crate::shutdown::{Shutdown, TaskGuard};
+use crate::utils::AppResult;
+
+/// 播报队列的最大长度，超出后丢弃新的播报
+const QUEUE_CAPACITY: usize = 32;
+
+/// 单条播报的最大字符数，避免超长文本长时间占用播报
+const MAX_TEXT_CHARS: usize = 200;
+
+/// 语音合成后端
+///
+/// `speak` 会阻塞到朗读结束，由播报任务在阻塞线程池中逐条调用
+pub trait TtsBackend: Send + Sync {
+    /// 后端名称，用于日志和事件
+    fn name(&self) -> &'static str;
+
+    /// 朗读文字
+    ///
+    /// # 参数
+    /// - `text`: 要朗读的文字
+    /// - `volume`: 音量，范围 0.0 ~ 1.0
+    fn speak(&self, text: &str, volume: f32) -> AppResult<()>;
+}
+
+/// 空后端：不发声，只打印日志
+///
+/// 用于没有语音合成能力的平台，也方便在没有声卡的环境中调试播报流程
+#[derive(Debug, Default)]
+pub struct NullBackend;
+
+impl TtsBackend for NullBackend {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+
+    fn speak(&self, text: &str, volume: f32) -> AppResult<()> {
+        tracing::info!(volume, text, "语音播报（无 TTS 后端）");
+        Ok(())
+    }
+}
+
+/// speech-dispatcher 后端，通过 spd-say 命令朗读
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default)]
+pub struct SpeechDispatcherBackend;
+
+#[cfg(target_os = "linux")]
+impl TtsBackend for SpeechDispatcherBackend {
+    fn name(&self) -> &'static str {
+        "speech-dispatcher"
+    }
+
+    fn speak(&self, text: &str, volume: f32) -> AppResult<()> {
+        // spd-say 的音量范围是 -100 ~ 100，--wait 等待朗读结束
+        let volume = (volume * 200.0 - 100.0).round() as i32;
+        run_tts_command(
+            Command::new("spd-say")
+                .arg("--wait")
+                .arg("--volume")
+                .arg(volume.to_string())
+                .arg("--")
+                .arg(text),
+        )
+    }
+}
+
+/// espeak 后端
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default)]
+pub struct EspeakBackend;
+
+#[cfg(target_os = "linux")]
+impl TtsBackend for EspeakBackend {
+    fn name(&self) -> &'static str {
+        "espeak"
+    }
+
+    fn speak(&self, text: &str, volume: f32) -> AppResult<()> {
+        // espeak 的音量范围是 0 ~ 200，默认 100；中文需要指定 zh 语音
+        let amplitude = (volume * 200.0).round() as i32;
+        run_tts_command(
+            Command::new("espeak")
+                .arg("-v")
+                .arg("zh")
+                .arg("-a")
+                .arg(amplitude.to_string())
+                .arg("--")
+                .arg(text),
+        )
+    }
+}
+
+/// 运行语音合成命令并等待结束
+#[cfg(target_os = "linux")]
+fn run_tts_command(command: &mut Command) -> AppResult<()> {
+    let status = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("TTS command exited with {}", status))
+    }
+}
+
+/// 判断命令是否可用
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn command_exists(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// 选择当前平台可用的语音合成后端
+pub fn default_backend() -> Box<dyn TtsBackend> {
+    #[cfg(target_os = "linux")]
+    {
+        if command_exists("spd-say") {
+            return Box::new(SpeechDispatcherBackend);
+        }
+        if command_exists("espeak") {
+            return Box::new(EspeakBackend);
+        }
+    }
+    Box::new(NullBackend)
+}
+
+/// 播报设置
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct VoiceSettings {
+    /// 音量，范围 0.0 ~ 1.0
+    pub volume: f32,
+    /// 是否静音，静音期间收到的播报直接丢弃
+    pub muted: bool,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+/// "voice-broadcast-played" 事件内容
+#[derive(Debug, Clone, Serialize)]
+struct PlayedPayload {
+    /// 播报的文字
+    text: String,
+    /// 使用的后端名称
+    backend: &'static str,
+    /// 是否真正朗读（静音时为 false）
+    played: bool,
+    /// 朗读失败时的错误信息
+    error: Option<String>,
+}
+
+/// 语音播报状态
+///
+/// 播报任务需要应用句柄来发送事件，所以在 setup 阶段创建并通过 `app.manage` 注册
+pub struct VoiceBroadcast {
+    /// 播报队列的发送端
+    sender: Sender<String>,
+    /// 播报设置，播报任务每次朗读前读取
+    settings: Arc<Mutex<VoiceSettings>>,
+}
+
+impl VoiceBroadcast {
+    /// 创建语音播报并启动播报任务
+    ///
+    /// # 参数
+    /// - `app`: Tauri 应用句柄，用于发送播报完成事件
+    /// - `backend`: 语音合成后端
+    pub fn new(app: tauri::AppHandle, backend: Box<dyn TtsBackend>) -> Self {
+        let shutdown = app.state::<Shutdown>().inner().clone();
+        Self::start(backend, &shutdown, move |payload| {
+            let _ = app.emit("voice-broadcast-played", payload);
+        })
+    }
+
+    /// 启动播报任务
+    ///
+    /// # 参数
+    /// - `backend`: 语音合成后端
+    /// - `shutdown`: 停止协调器，播报任务登记到其中
+    /// - `emit`: 每条播报处理完成后调用，发送 "voice-broadcast-played" 事件
+    fn start(
+        backend: Box<dyn TtsBackend>,
+        shutdown: &Shutdown,
+        emit: impl Fn(PlayedPayload) + Send + Sync + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let settings = Arc::new(Mutex::new(VoiceSettings::default()));
+
+        tracing::info!(backend = backend.name(), "语音播报后端已选择");
+        let worker = Worker {
+            backend: Arc::from(backend),
+            settings: settings.clone(),
+            emit: Arc::new(emit),
+        };
+        let guard = shutdown.register("voice_broadcast");
+        tauri::async_runtime::spawn(worker.run(guard, receiver, shutdown.token()));
+
+        Self { sender, settings }
+    }
+
+    /// 把播报文字加入队列
+    pub fn enqueue(&self, text: &str) -> AppResult<()> {
+        let text: String = text.trim().chars().take(MAX_TEXT_CHARS).collect();
+        if text.is_empty() {
+            return Ok(());
+        }
+        match self.sender.try_send(text) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err("Voice broadcast queue is full".to_string()),
+            Err(TrySendError::Closed(_)) => Err("Voice broadcast worker stopped".to_string()),
+        }
+    }
+
+    /// 获取播报设置
+    pub fn settings(&self) -> AppResult<VoiceSettings> {
+        self.settings
+            .lock()
+            .map(|settings| *settings)
+            .map_err(|e| e.to_string())
+    }
+
+    /// 修改播报设置
+    fn update(&self, f: impl FnOnce(&mut VoiceSettings)) -> AppResult<VoiceSettings> {
+        let mut settings = self.settings.lock().map_err(|e| e.to_string())?;
+        f(&mut settings);
+        Ok(*settings)
+    }
+}
+
+/// 播报任务的状态
+struct Worker<E> {
+    /// 语音合成后端
+    backend: Arc<dyn TtsBackend>,
+    /// 播报设置
+    settings: Arc<Mutex<VoiceSettings>>,
+    /// 播报完成回调
+    emit: Arc<E>,
+}
+
+impl<E: Fn(PlayedPayload) + Send + Sync + 'static> Worker<E> {
+    /// 从队列中逐条取出文字并朗读，收到取消信号或队列关闭后退出
+    ///
+    /// 参数按声明的逆序销毁，登记凭证放在队列之前，保证停止协调器看到任务结束时队列已经关闭
+    async fn run(
+        self,
+        _guard: TaskGuard,
+        mut receiver: Receiver<String>,
+        token: CancellationToken,
+    ) {
+        loop {
+            let text = tokio::select! {
+                biased;
+                _ = token.cancelled() => break,
+                text = receiver.recv() => match text {
+                    Some(text) => text,
+                    None => break,
+                },
+            };
+            // 朗读不响应取消信号，读完当前这条后在下一轮循环退出
+            let backend = self.backend.clone();
+            let settings = self.settings.lock().map(|s| *s).unwrap_or_default();
+            let emit = self.emit.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                emit(play(backend.as_ref(), text, settings));
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "语音播报任务异常结束");
+            }
+        }
+    }
+}
+
+/// 按设置朗读一条播报，返回播报完成事件的内容
+fn play(backend: &dyn TtsBackend, text: String, settings: VoiceSettings) -> PlayedPayload {
+    let (played, error) = if settings.muted {
+        (false, None)
+    } else {
+        match backend.speak(&text, settings.volume) {
+            Ok(()) => (true, None),
+            Err(e) => {
+                tracing::warn!(error = %e, "语音播报失败");
+                (false, Some(e))
+            }
+        }
+    };
+    PlayedPayload {
+        text,
+        backend: backend.name(),
+        played,
+        error,
+    }
+}
+
+/// 从 EventCommon.data 中取出播报文字
+///
+/// data 一般是 `{"text": "..."}` 或 `{"content": "..."}` 形式的 JSON，
+/// 不是 JSON 时整体作为播报文字
+fn broadcast_text(data: &str) -> String {
+    #[derive(Deserialize)]
+    struct Broadcast {
+        #[serde(alias = "content")]
+        text: String,
+    }
+
+    serde_json::from_str::<Broadcast>(data)
+        .map(|broadcast| broadcast.text)
+        .unwrap_or_else(|_| data.to_string())
+}
+
+/// 处理 EventCommon 类型 2：语音播报
+///
+/// # 参数
+/// - `data`: 事件内容
+pub fn handle_broadcast_event(app: &tauri::AppHandle, data: &str) -> AppResult<()> {
+    let state = app.state::<VoiceBroadcast>();
+    state.enqueue(&broadcast_text(data))
+}
+
+/// 获取语音播报设置
+#[tauri::command]
+pub fn get_voice_broadcast_settings(state: State<VoiceBroadcast>) -> AppResult<VoiceSettings> {
+    state.settings()
+}
+
+/// 设置语音播报音量
+///
+/// # 参数
+/// - `volume`: 音量，范围 0.0 ~ 1.0，超出范围会被截断
+#[tauri::command]
+pub fn set_voice_broadcast_volume(
+    state: State<VoiceBroadcast>,
+    volume: f32,
+) -> AppResult<VoiceSettings> {
+    if !volume.is_finite() {
+        return Err("Invalid volume".to_string());
+    }
+    state.update(|settings| settings.volume = volume.clamp(0.0, 1.0))
+}
+
+/// 设置语音播报是否静音
+#[tauri::command]
+pub fn set_voice_broadcast_muted(
+    state: State<VoiceBroadcast>,
+    muted: bool,
+) -> AppResult<VoiceSettings> {
+    state.update(|settings| settings.muted = muted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // 播报任务运行在异步运行时中，测试使用多线程运行时，测试线程阻塞等待事件时播报任务仍能执行
+
+    /// 等待一条播报完成事件
+    const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// 使用空后端启动播报，播报完成事件发送到返回的接收端
+    fn start_null() -> (VoiceBroadcast, mpsc::Receiver<PlayedPayload>) {
+        let (tx, rx) = mpsc::channel();
+        let broadcast = VoiceBroadcast::start(Box::new(NullBackend), &Shutdown::new(), move |p| {
+            let _ = tx.send(p);
+        });
+        (broadcast, rx)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn plays_in_enqueue_order() {
+        let (broadcast, rx) = start_null();
+        for text in ["第一条", "第二条", "第三条"] {
+            broadcast.enqueue(text).unwrap();
+        }
+        let played: Vec<String> = (0..3)
+            .map(|_| rx.recv_timeout(RECV_TIMEOUT).unwrap().text)
+            .collect();
+        assert_eq!(played, ["第一条", "第二条", "第三条"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trims_and_truncates_text() {
+        let (broadcast, rx) = start_null();
+        broadcast.enqueue("   ").unwrap();
+        broadcast
+            .enqueue(&"长".repeat(MAX_TEXT_CHARS + 10))
+            .unwrap();
+        let payload = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        assert_eq!(payload.text.chars().count(), MAX_TEXT_CHARS);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn muted_broadcast_is_not_played() {
+        let (broadcast, rx) = start_null();
+        broadcast.update(|settings| settings.muted = true).unwrap();
+        broadcast.enqueue("静音").unwrap();
+        let payload = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        assert_eq!(payload.text, "静音");
+        assert!(!payload.played);
+        assert!(payload.error.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejects_broadcast_when_queue_is_full() {
+        // 第一条播报的完成事件阻塞播报线程，直到测试放行
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let broadcast = VoiceBroadcast::start(Box::new(NullBackend), &Shutdown::new(), move |_| {
+            let _ = started_tx.send(());
+            let _ = release_rx.lock().unwrap().recv();
+        });
+
+        broadcast.enqueue("占用").unwrap();
+        started_rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        for i in 0..QUEUE_CAPACITY {
+            broadcast.enqueue(&format!("排队 {}", i)).unwrap();
+        }
+        assert!(broadcast.enqueue("丢弃").is_err());
+        drop(release_tx);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn idle_worker_stops_on_shutdown() {
+        let shutdown = Shutdown::new();
+        let broadcast = VoiceBroadcast::start(Box::new(NullBackend), &shutdown, |_| {});
+        shutdown.shutdown().await;
+        // 播报任务结束后队列的接收端已经关闭
+        assert!(broadcast.enqueue("退出后").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn played_payload_format() {
+        let (broadcast, rx) = start_null();
+        broadcast.enqueue("你好").unwrap();
+        let payload = rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "text": "你好",
+                "backend": "null",
+                "played": true,
+                "error": null,
+            })
+        );
+    }
+}
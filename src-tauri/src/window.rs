@@ -1,9 +1,343 @@
-use tauri::Manager;
-
 /// 窗口管理相关功能模块
 ///
-/// 提供窗口显示、隐藏、标题更新等功能
+/// 提供窗口打开、显示、隐藏、标题更新等功能
 /// 这个模块封装了与窗口操作相关的所有逻辑，避免重复代码
+///
+/// 所有窗口都通过标签管理：
+/// - `main`: 主窗口，由 tauri.conf.json 创建，关闭时只隐藏
+/// - `features-window`、`about-window`: 功能演示和关于窗口
+/// - `notice-window`: 系统公告弹窗
+/// - `chat-user-<用户 id>`、`chat-room-<群 id>`: 弹出的聊天窗口，见 chat_windows 模块
+///
+/// `WindowRegistry` 记录当前打开的窗口，窗口销毁时自动移除；
+/// 已打开的窗口再次打开时直接显示并聚焦，不会重复创建
+///
+/// 所有窗口的大小、位置、最大化和全屏状态由 window-state 插件保存，下次创建时恢复；
+/// 窗口的显示与否不由插件恢复，见 `window_state_flags`
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{LogicalSize, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+
+use crate::error::AppError;
+
+/// 主窗口标签
+pub const MAIN_WINDOW_LABEL: &str = "main";
+
+/// 功能演示窗口标签
+pub const FEATURES_WINDOW_LABEL: &str = "features-window";
+
+/// 关于窗口标签
+pub const ABOUT_WINDOW_LABEL: &str = "about-window";
+
+/// 聊天弹出窗口标签前缀
+pub const CHAT_WINDOW_PREFIX: &str = "chat-";
+
+/// 判断窗口是否在屏幕上时，窗口顶部（标题栏）至少需要露出的高度
+const MIN_VISIBLE_TOP: i32 = 32;
+
+/// 判断窗口是否在屏幕上时，窗口至少需要露出的宽度
+const MIN_VISIBLE_WIDTH: i32 = 100;
+
+/// 窗口配置
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowSpec {
+    /// 窗口标签
+    pub label: String,
+    /// 窗口标题
+    pub title: String,
+    /// 前端路由地址
+    pub url: String,
+    /// 窗口宽度
+    pub width: f64,
+    /// 窗口高度
+    pub height: f64,
+    /// 是否可以调整大小
+    pub resizable: bool,
+    /// 是否是对话框样式（不能最小化和最大化）
+    pub dialog: bool,
+    /// 是否置顶
+    pub always_on_top: bool,
+}
+
+impl WindowSpec {
+    /// 普通窗口配置
+    fn new(label: &str, title: &str, url: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            title: title.to_string(),
+            url: url.to_string(),
+            width: 800.0,
+            height: 600.0,
+            resizable: true,
+            dialog: false,
+            always_on_top: false,
+        }
+    }
+
+    /// 主窗口，由 tauri.conf.json 创建
+    pub fn main() -> Self {
+        Self::new(MAIN_WINDOW_LABEL, "Demo", "/")
+    }
+
+    /// 功能演示窗口
+    pub fn features() -> Self {
+        Self::new(FEATURES_WINDOW_LABEL, "Tauri功能演示", "/features")
+    }
+
+    /// 关于窗口
+    pub fn about() -> Self {
+        Self::new(ABOUT_WINDOW_LABEL, "Tauri关于", "/about")
+    }
+
+    /// 聊天弹出窗口，每个会话一个窗口
+    ///
+    /// # 参数
+    /// - `target_id`: 用户 id 或群 id
+    /// - `is_room`: 是否是群聊
+    /// - `title`: 窗口标题，一般是对方昵称或群名称
+    pub fn chat(target_id: u64, is_room: bool, title: &str) -> Self {
+        Self {
+            width: 480.0,
+            height: 640.0,
+            ..Self::new(
+                &chat_window_label(target_id, is_room),
+                title,
+                &format!("/chat?id={}&is_room={}", target_id, is_room),
+            )
+        }
+    }
+
+    /// 按标签获取固定窗口的配置，聊天窗口和公告窗口需要额外参数，不在这里
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            MAIN_WINDOW_LABEL => Some(Self::main()),
+            FEATURES_WINDOW_LABEL => Some(Self::features()),
+            ABOUT_WINDOW_LABEL => Some(Self::about()),
+            _ => None,
+        }
+    }
+}
+
+/// 聊天弹出窗口的标签
+///
+/// 单聊和群聊的 id 可能相同，标签中区分会话类型
+pub fn chat_window_label(target_id: u64, is_room: bool) -> String {
+    let kind = if is_room { "room" } else { "user" };
+    format!("{}{}-{}", CHAT_WINDOW_PREFIX, kind, target_id)
+}
+
+/// 已打开窗口的信息
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenWindow {
+    /// 窗口标签
+    pub label: String,
+    /// 窗口标题
+    pub title: String,
+    /// 是否可见
+    pub visible: bool,
+    /// 是否有焦点
+    pub focused: bool,
+}
+
+/// 窗口注册表，记录当前打开的窗口
+#[derive(Debug, Default)]
+pub struct WindowRegistry {
+    /// 按标签索引的窗口配置
+    open: Mutex<BTreeMap<String, WindowSpec>>,
+}
+
+impl WindowRegistry {
+    /// 创建空的窗口注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录窗口已打开
+    fn insert(&self, spec: WindowSpec) {
+        if let Ok(mut open) = self.open.lock() {
+            open.insert(spec.label.clone(), spec);
+        }
+    }
+
+    /// 记录窗口已销毁
+    fn remove(&self, label: &str) {
+        if let Ok(mut open) = self.open.lock() {
+            open.remove(label);
+        }
+    }
+
+    /// 已打开窗口的配置
+    pub fn specs(&self) -> Result<Vec<WindowSpec>, AppError> {
+        Ok(self.open.lock()?.values().cloned().collect())
+    }
+}
+
+/// 把窗口加入注册表，并在窗口销毁时移除
+///
+/// # 参数
+/// - `window`: 已创建的窗口
+/// - `spec`: 窗口配置
+pub fn track_window(app: &tauri::AppHandle, window: &WebviewWindow, spec: WindowSpec) {
+    let label = spec.label.clone();
+    app.state::<WindowRegistry>().insert(spec);
+
+    let app_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            tracing::debug!(label = %label, "窗口已销毁");
+            app_handle.state::<WindowRegistry>().remove(&label);
+        }
+    });
+}
+
+/// window-state 插件保存和恢复的窗口状态
+///
+/// 不包含 VISIBLE：主窗口关闭时只隐藏到托盘，如果按退出时的可见状态恢复，
+/// 下次启动时就看不到主窗口了。主窗口由前端加载完成后显示，
+/// 聊天弹出窗口由 chat_windows 模块按上次打开的列表恢复
+pub fn window_state_flags() -> StateFlags {
+    StateFlags::all() - StateFlags::VISIBLE
+}
+
+/// 立即保存所有窗口的状态
+///
+/// 插件只在窗口关闭和应用退出时保存，主窗口隐藏到托盘时需要手动保存，
+/// 避免之后应用被强制结束时丢失窗口位置
+pub fn save_window_state(app: &tauri::AppHandle) {
+    if let Err(e) = app.save_window_state(window_state_flags()) {
+        tracing::warn!(error = %e, "保存窗口状态失败");
+    }
+}
+
+/// 确保窗口在某个显示器内，否则移动到当前显示器中央
+///
+/// 上次保存的位置可能位于已经断开的显示器上，或者因为分辨率变化跑到屏幕外面，
+/// 窗口顶部露出的部分不足以拖动时视为不在屏幕上
+pub fn ensure_on_screen(window: &WebviewWindow) {
+    let (Ok(position), Ok(size), Ok(monitors)) = (
+        window.outer_position(),
+        window.outer_size(),
+        window.available_monitors(),
+    ) else {
+        return;
+    };
+
+    let on_screen = monitors.iter().any(|monitor| {
+        let origin = monitor.position();
+        let extent = monitor.size();
+        let left = position.x.max(origin.x);
+        let right = (position.x + size.width as i32).min(origin.x + extent.width as i32);
+        let top_visible = position.y >= origin.y
+            && position.y + MIN_VISIBLE_TOP <= origin.y + extent.height as i32;
+        top_visible && right - left >= MIN_VISIBLE_WIDTH
+    });
+    if !on_screen {
+        tracing::info!(label = %window.label(), x = position.x, y = position.y, "窗口不在屏幕内，移动到屏幕中央");
+        let _ = window.center();
+    }
+}
+
+/// 获取主窗口
+pub fn main_window(app: &tauri::AppHandle) -> Option<WebviewWindow> {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+}
+
+/// 按标签获取窗口，不存在时返回 `AppError::WindowNotFound`
+fn get_window(app: &tauri::AppHandle, label: &str) -> Result<WebviewWindow, AppError> {
+    app.get_webview_window(label)
+        .ok_or_else(|| AppError::WindowNotFound(label.to_string()))
+}
+
+/// 打开窗口：已存在时显示并聚焦，否则按配置创建
+///
+/// 在 Windows 上同步命令中创建窗口会死锁，调用这个函数的命令必须是 async
+///
+/// # 返回值
+/// - `Ok((窗口, true))`: 新创建的窗口
+/// - `Ok((窗口, false))`: 复用已存在的窗口
+pub fn open_window(
+    app: &tauri::AppHandle,
+    spec: WindowSpec,
+) -> Result<(WebviewWindow, bool), AppError> {
+    if let Some(window) = app.get_webview_window(&spec.label) {
+        show_window(app, &spec.label)?;
+        return Ok((window, false));
+    }
+
+    tracing::debug!(label = %spec.label, url = %spec.url, "创建窗口");
+    let window =
+        WebviewWindowBuilder::new(app, &spec.label, WebviewUrl::App(spec.url.clone().into()))
+            .title(&spec.title)
+            .inner_size(spec.width, spec.height)
+            .resizable(spec.resizable)
+            .minimizable(!spec.dialog)
+            .maximizable(!spec.dialog)
+            .always_on_top(spec.always_on_top)
+            .center()
+            .build()?;
+    // 创建时 window-state 插件已经恢复了上次的位置
+    ensure_on_screen(&window);
+    track_window(app, &window, spec);
+    Ok((window, true))
+}
+
+/// 显示窗口并设置焦点
+///
+/// # 参数
+/// - `label`: 窗口标签
+pub fn show_window(app: &tauri::AppHandle, label: &str) -> Result<(), AppError> {
+    let window = get_window(app, label)?;
+    // 窗口隐藏期间显示器可能发生了变化
+    ensure_on_screen(&window);
+    // show() 显示窗口，如果窗口已经显示则无效果
+    window.show()?;
+    // unminimize() 如果窗口被最小化则取消最小化
+    window.unminimize()?;
+    // set_focus() 将焦点设置到窗口，使其成为活动窗口
+    window.set_focus()?;
+    Ok(())
+}
+
+/// 隐藏窗口
+///
+/// 隐藏后窗口仍然存在，再次显示时保留原来的状态
+///
+/// # 参数
+/// - `label`: 窗口标签
+pub fn hide_window(app: &tauri::AppHandle, label: &str) -> Result<(), AppError> {
+    // hide() 隐藏窗口但不关闭应用程序
+    // 这与 close() 不同，close() 会关闭窗口并可能退出应用
+    get_window(app, label)?.hide()?;
+    Ok(())
+}
+
+/// 切换窗口显示状态：可见时隐藏，隐藏时显示并聚焦
+///
+/// # 参数
+/// - `label`: 窗口标签
+pub fn toggle_window(app: &tauri::AppHandle, label: &str) -> Result<(), AppError> {
+    // is_visible() 返回 Result<bool, Error>
+    // unwrap_or(false) 表示如果获取可见性状态失败，则假设窗口不可见
+    if get_window(app, label)?.is_visible().unwrap_or(false) {
+        hide_window(app, label)
+    } else {
+        show_window(app, label)
+    }
+}
+
+/// 关闭窗口，窗口不存在时忽略
+///
+/// # 参数
+/// - `label`: 窗口标签
+pub fn close_window(app: &tauri::AppHandle, label: &str) -> Result<(), AppError> {
+    if let Some(window) = app.get_webview_window(label) {
+        window.close()?;
+    }
+    Ok(())
+}
 
 /// 更新窗口标题显示未读数
 ///
@@ -11,28 +345,30 @@ use tauri::Manager;
 /// 如果有未读消息，会在标题中显示红色圆点和未读数量
 /// 如果没有未读消息，显示普通的应用标题
 ///
+/// 与托盘一样尽力更新，主窗口不存在（如已被关闭）时记录警告后跳过
+///
 /// # 参数
 /// - `app`: Tauri 应用句柄
 /// - `count`: 未读消息数量
-///
-/// # 返回值
-/// - `Ok(())`: 操作成功
-/// - `Err(String)`: 操作失败，包含错误信息
-pub fn update_window_title(app: &tauri::AppHandle, count: u32) -> Result<(), String> {
+pub fn update_window_title(app: &tauri::AppHandle, count: u32) {
     // 更新窗口标题显示未读数
-    // 使用工具函数获取主窗口，避免重复的窗口获取逻辑
-    if let Some(window) = app.get_webview_window("main") {
-        // 根据未读数量创建不同的窗口标题
-        let window_title = if count > 0 {
-            format!("🔴 Demo ({} 条未读)", count)
-        } else {
-            "Demo".to_string()
-        };
-        // 设置窗口标题，忽略可能的错误
-        // 这里使用 let _ = 是因为窗口标题设置失败通常不会影响应用程序的核心功能
-        let _ = window.set_title(&window_title);
-    }
-    Ok(())
+    let window = match get_window(app, MAIN_WINDOW_LABEL) {
+        Ok(window) => window,
+        Err(e) => {
+            tracing::warn!(error = %e, "主窗口不存在，跳过更新窗口标题");
+            return;
+        }
+    };
+
+    // 根据未读数量创建不同的窗口标题
+    let window_title = if count > 0 {
+        format!("🔴 Demo ({} 条未读)", count)
+    } else {
+        "Demo".to_string()
+    };
+    // 设置窗口标题，忽略可能的错误
+    // 这里使用 let _ = 是因为窗口标题设置失败通常不会影响应用程序的核心功能
+    let _ = window.set_title(&window_title);
 }
 
 /// 显示主窗口并设置焦点
@@ -43,19 +379,9 @@ pub fn update_window_title(app: &tauri::AppHandle, count: u32) -> Result<(), Str
 /// # 参数
 /// - `app`: Tauri 应用句柄
 pub fn show_main_window(app: &tauri::AppHandle) {
-    // 获取名为 "main" 的 WebView 窗口
-    // get_webview_window 返回 Option<WebviewWindow>
-    if let Some(window) = app.get_webview_window("main") {
-        println!("显示主窗口并设置焦点");
-        // show() 显示窗口，如果窗口已经显示则无效果
-        let _ = window.show();
-        // set_focus() 将焦点设置到窗口，使其成为活动窗口
-        let _ = window.set_focus();
-        // unminimize() 如果窗口被最小化则取消最小化
-        let _ = window.unminimize();
-        println!("主窗口已显示并获得焦点");
-    } else {
-        println!("警告：无法找到名为 'main' 的窗口");
+    tracing::debug!("显示主窗口并设置焦点");
+    if let Err(e) = show_window(app, MAIN_WINDOW_LABEL) {
+        tracing::warn!(error = %e, "显示主窗口失败");
     }
 }
 
@@ -67,14 +393,9 @@ pub fn show_main_window(app: &tauri::AppHandle) {
 /// # 参数
 /// - `app`: Tauri 应用句柄
 pub fn hide_main_window(app: &tauri::AppHandle) {
-    if let Some(window) = app.get_webview_window("main") {
-        println!("隐藏主窗口");
-        // hide() 隐藏窗口但不关闭应用程序
-        // 这与 close() 不同，close() 会关闭窗口并可能退出应用
-        let _ = window.hide();
-        println!("主窗口已隐藏");
-    } else {
-        println!("警告：无法找到名为 'main' 的窗口");
+    tracing::debug!("隐藏主窗口");
+    if let Err(e) = hide_window(app, MAIN_WINDOW_LABEL) {
+        tracing::warn!(error = %e, "隐藏主窗口失败");
     }
 }
 
@@ -89,16 +410,82 @@ pub fn hide_main_window(app: &tauri::AppHandle) {
 /// # 参数
 /// - `app`: Tauri 应用句柄
 pub fn toggle_main_window(app: &tauri::AppHandle) {
-    if let Some(window) = app.get_webview_window("main") {
-        // 检查窗口是否可见
-        // is_visible() 返回 Result<bool, Error>
-        // unwrap_or(false) 表示如果获取可见性状态失败，则假设窗口不可见
-        if window.is_visible().unwrap_or(false) {
-            // 窗口当前可见，隐藏它
-            hide_main_window(app);
-        } else {
-            // 窗口当前隐藏，显示它
-            show_main_window(app);
+    if let Err(e) = toggle_window(app, MAIN_WINDOW_LABEL) {
+        tracing::warn!(error = %e, "切换主窗口显示状态失败");
+    }
+}
+
+/// 打开固定窗口（features-window、about-window 等）
+///
+/// # 参数
+/// - `label`: 窗口标签
+#[tauri::command]
+pub async fn open_app_window(app: tauri::AppHandle, label: String) -> Result<(), AppError> {
+    let spec = WindowSpec::from_label(&label).ok_or(AppError::WindowNotFound(label))?;
+    open_window(&app, spec)?;
+    Ok(())
+}
+
+/// 显示并聚焦窗口
+#[tauri::command]
+pub fn show_app_window(app: tauri::AppHandle, label: String) -> Result<(), AppError> {
+    show_window(&app, &label)
+}
+
+/// 隐藏窗口
+#[tauri::command]
+pub fn hide_app_window(app: tauri::AppHandle, label: String) -> Result<(), AppError> {
+    hide_window(&app, &label)
+}
+
+/// 切换窗口显示状态
+#[tauri::command]
+pub fn toggle_app_window(app: tauri::AppHandle, label: String) -> Result<(), AppError> {
+    toggle_window(&app, &label)
+}
+
+/// 重置窗口布局：所有打开的窗口恢复默认大小并居中，然后保存窗口状态
+///
+/// 没有打开的窗口保留原来保存的状态，下次打开时仍会检查是否在屏幕内
+#[tauri::command]
+pub fn reset_window_layout(
+    app: tauri::AppHandle,
+    registry: State<WindowRegistry>,
+) -> Result<(), AppError> {
+    for spec in registry.specs()? {
+        let Some(window) = app.get_webview_window(&spec.label) else {
+            continue;
+        };
+        window.set_fullscreen(false)?;
+        if window.is_maximized().unwrap_or(false) {
+            window.unmaximize()?;
         }
+        window.set_size(LogicalSize::new(spec.width, spec.height))?;
+        window.center()?;
     }
+    app.save_window_state(window_state_flags())
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    tracing::info!("窗口布局已重置");
+    Ok(())
+}
+
+/// 获取当前打开的窗口
+#[tauri::command]
+pub fn list_open_windows(
+    app: tauri::AppHandle,
+    registry: State<WindowRegistry>,
+) -> Result<Vec<OpenWindow>, AppError> {
+    Ok(registry
+        .specs()?
+        .into_iter()
+        .filter_map(|spec| {
+            let window = app.get_webview_window(&spec.label)?;
+            Some(OpenWindow {
+                visible: window.is_visible().unwrap_or(false),
+                focused: window.is_focused().unwrap_or(false),
+                label: spec.label,
+                title: window.title().unwrap_or(spec.title),
+            })
+        })
+        .collect())
 }
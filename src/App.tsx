@@ -1,16 +1,49 @@
 import './App.css'
 import { useEffect } from 'react'
 import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow'
+import { appAPI } from './jsBridge/app'
+import { logAPI } from './jsBridge/log'
+import { deepLinkAPI, DeepLink } from './jsBridge/deepLink'
+import { windowAPI } from './jsBridge/window'
 
 // 如果以后需要路由功能，取消下面的注释
 import { BrowserRouter, Routes, Route } from 'react-router-dom'
 import Home from './pages/Home'
 import Features from './pages/Features'
 import About from './pages/About'
+import NoticePage from './pages/Notice'
+import ChatPage from './pages/Chat'
+
+/** 处理主窗口收到的深度链接，公告已由 Rust 打开 */
+function handleDeepLink(link: DeepLink) {
+    switch (link.type) {
+        case 'open_conversation':
+            windowAPI.openChatWindow(
+                link.target_id,
+                link.is_room,
+                link.is_room ? `群聊 ${link.target_id}` : `私聊 ${link.target_id}`
+            )
+            break
+        case 'join_call':
+            // 演示应用没有通话功能
+            console.log('加入通话', link.room_id)
+            break
+    }
+}
 
 function App() {
     useEffect(() => {
-        getCurrentWebviewWindow().show()
+        const currentWindow = getCurrentWebviewWindow()
+        currentWindow.show()
+
+        // 退出前由主窗口等待还没写完的日志，之后通知 Rust 继续退出
+        if (currentWindow.label !== 'main') return
+        const unlistenWillQuit = appAPI.onWillQuit(() => logAPI.flushLogs())
+        const unlistenDeepLink = deepLinkAPI.onDeepLink(handleDeepLink)
+        return () => {
+            unlistenWillQuit.then(fn => fn())
+            unlistenDeepLink.then(fn => fn())
+        }
     }, [])
 
     return (
@@ -20,6 +53,8 @@ function App() {
                     <Route path="/" element={<Home />} />
                     <Route path="/features" element={<Features />} />
                     <Route path="/about" element={<About />} />
+                    <Route path="/notice" element={<NoticePage />} />
+                    <Route path="/chat" element={<ChatPage />} />
                 </Routes>
             </BrowserRouter>
         </div>
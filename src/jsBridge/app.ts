@@ -1,15 +1,35 @@
 /**
  * 应用程序相关 API 抽象层
- * 提供应用版本、标识符等信息获取功能
+ * 提供应用版本、标识符等信息获取功能，以及关闭行为设置和退出流程
+ * 关闭行为保存在应用设置中，这里的接口只读写其中的 close_behavior 和 tray_hint_shown
  */
 
 import { getVersion, getIdentifier } from '@tauri-apps/api/app'
+import { listen, UnlistenFn } from '@tauri-apps/api/event'
+import { coreAPI } from './core'
+import type { CloseBehavior } from './settings'
+
+export interface CloseSettings {
+    behavior: CloseBehavior
+    tray_hint_shown: boolean
+}
 
 export interface AppAPI {
     /** 获取应用程序版本 */
     getVersion(): Promise<string>
     /** 获取应用程序标识符 */
     getIdentifier(): Promise<string | null>
+    /** 获取关闭行为设置 */
+    getCloseSettings(): Promise<CloseSettings>
+    /** 设置主窗口关闭按钮的行为 */
+    setCloseBehavior(behavior: CloseBehavior): Promise<CloseSettings>
+    /** 退出应用，与托盘菜单的“退出”相同 */
+    quit(): Promise<void>
+    /**
+     * 注册退出前的处理函数（如等待日志写完）
+     * 处理完成后通知 Rust 继续退出，Rust 侧最多等待 3 秒
+     */
+    onWillQuit(handler: () => Promise<void> | void): Promise<UnlistenFn>
 }
 
 export const appAPI: AppAPI = {
@@ -19,5 +39,27 @@ export const appAPI: AppAPI = {
 
     getIdentifier: async () => {
         return await getIdentifier()
+    },
+
+    getCloseSettings: async () => {
+        return await coreAPI.invoke<CloseSettings>('get_close_settings')
+    },
+
+    setCloseBehavior: async (behavior: CloseBehavior) => {
+        return await coreAPI.invoke<CloseSettings>('set_close_behavior', { behavior })
+    },
+
+    quit: async () => {
+        await coreAPI.invoke('quit_app')
+    },
+
+    onWillQuit: async (handler: () => Promise<void> | void) => {
+        return await listen('app-will-quit', async () => {
+            try {
+                await handler()
+            } finally {
+                await coreAPI.invoke('confirm_quit')
+            }
+        })
     }
 }
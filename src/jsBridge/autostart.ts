@@ -1,9 +1,12 @@
 /**
  * 自动启动相关 API 抽象层
  * 提供跨平台的自动启动功能
+ * 开机自启是应用设置的一部分，开启和关闭通过 settings 修改，由 Rust 修改系统状态并保存，
+ * 前端没有直接修改系统自启的权限
  */
 
-import { enable, disable, isEnabled } from '@tauri-apps/plugin-autostart'
+import { isEnabled } from '@tauri-apps/plugin-autostart'
+import { settingsAPI } from './settings'
 
 export interface AutostartAPI {
     /** 启用自动启动 */
@@ -16,11 +19,11 @@ export interface AutostartAPI {
 
 export const autostartAPI: AutostartAPI = {
     enable: async () => {
-        return await enable()
+        await settingsAPI.patch({ autostart: true })
     },
 
     disable: async () => {
-        return await disable()
+        await settingsAPI.patch({ autostart: false })
     },
 
     isEnabled: async () => {
@@ -5,6 +5,26 @@
 
 import { invoke } from '@tauri-apps/api/core'
 
+/** 后端命令返回的结构化错误 */
+export interface AppError {
+    /** 稳定的错误码，如 LOCK_POISONED、BASE64_DECODE、TRAY_NOT_FOUND */
+    code: string
+    /** 可读的错误描述 */
+    message: string
+    /** 附加信息 */
+    details: string | null
+}
+
+/** 判断是否是后端返回的结构化错误 */
+export function isAppError(error: unknown): error is AppError {
+    return typeof error === 'object' && error !== null && 'code' in error && 'message' in error
+}
+
+/** 获取错误描述，兼容结构化错误和尚未迁移的字符串错误 */
+export function errorMessage(error: unknown): string {
+    return isAppError(error) ? `[${error.code}] ${error.message}` : String(error)
+}
+
 export interface CoreAPI {
     /** 调用后端命令 */
     invoke<T = any>(command: string, args?: Record<string, any>): Promise<T>
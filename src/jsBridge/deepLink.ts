@@ -0,0 +1,30 @@
+/**
+ * 深度链接 API 抽象层
+ * 启动参数中的深度链接（如 demo://chat/<room_id>）由 Rust 解析，
+ * 公告由 Rust 直接打开，打开会话、加入通话等交给主窗口处理
+ */
+
+import { listen, UnlistenFn } from '@tauri-apps/api/event'
+import { coreAPI } from './core'
+
+export type DeepLink =
+    | { type: 'open_conversation'; target_id: number; is_room: boolean }
+    | { type: 'join_call'; room_id: number }
+    | { type: 'open_notice'; notice_id: number }
+
+export interface DeepLinkAPI {
+    /**
+     * 处理深度链接，只在主窗口中调用一次
+     * 先注册事件监听，再处理前端加载完成前收到的链接
+     */
+    onDeepLink(handler: (link: DeepLink) => void): Promise<UnlistenFn>
+}
+
+export const deepLinkAPI: DeepLinkAPI = {
+    onDeepLink: async (handler: (link: DeepLink) => void) => {
+        const unlisten = await listen<DeepLink>('deep-link', event => handler(event.payload))
+        const pending = await coreAPI.invoke<DeepLink[]>('take_pending_deep_links')
+        pending.forEach(handler)
+        return unlisten
+    }
+}
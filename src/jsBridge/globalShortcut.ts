@@ -1,12 +1,28 @@
 /**
  * 全局快捷键相关 API 抽象层
  * 提供全局快捷键注册、注销等功能
+ * 应用内置的快捷键（显示/隐藏主窗口、清除未读数、截图）由 Rust 按设置注册，在设置的 shortcuts 中修改
  */
 
 import { register, unregister, isRegistered } from '@tauri-apps/plugin-global-shortcut'
+import { listen, UnlistenFn } from '@tauri-apps/api/event'
+import { coreAPI } from './core'
 
 export type ShortcutHandler = () => void | Promise<void>
 
+/** 应用内置快捷键的动作 */
+export type ShortcutAction = 'toggle_main_window' | 'clear_unread' | 'screenshot'
+
+/** 应用内置快捷键的注册结果 */
+export interface ShortcutStatus {
+    action: ShortcutAction
+    /** 设置中的组合键，为空表示已关闭 */
+    shortcut: string
+    registered: boolean
+    /** 注册失败的原因，如组合键已被其他应用占用 */
+    error: string | null
+}
+
 export interface GlobalShortcutAPI {
     /** 注册全局快捷键 */
     register(accelerator: string, handler: ShortcutHandler): Promise<void>
@@ -14,6 +30,12 @@ export interface GlobalShortcutAPI {
     unregister(accelerator: string): Promise<void>
     /** 检查快捷键是否已注册 */
     isRegistered(accelerator: string): Promise<boolean>
+    /** 获取应用内置快捷键的注册结果 */
+    getStatus(): Promise<ShortcutStatus[]>
+    /** 监听应用内置快捷键注册结果的变化 */
+    onStatusChanged(handler: (status: ShortcutStatus[]) => void): Promise<UnlistenFn>
+    /** 监听应用内置快捷键被触发，截图等动作需要前端处理 */
+    onTriggered(handler: (action: ShortcutAction) => void): Promise<UnlistenFn>
 }
 
 export const globalShortcutAPI: GlobalShortcutAPI = {
@@ -27,5 +49,19 @@ export const globalShortcutAPI: GlobalShortcutAPI = {
 
     isRegistered: async (accelerator: string) => {
         return await isRegistered(accelerator)
+    },
+
+    getStatus: async () => {
+        return await coreAPI.invoke<ShortcutStatus[]>('get_shortcut_status')
+    },
+
+    onStatusChanged: async (handler: (status: ShortcutStatus[]) => void) => {
+        return await listen<ShortcutStatus[]>('shortcut-status-changed', event => handler(event.payload))
+    },
+
+    onTriggered: async (handler: (action: ShortcutAction) => void) => {
+        return await listen<{ action: ShortcutAction }>('global-shortcut-triggered', event =>
+            handler(event.payload.action)
+        )
     }
 }
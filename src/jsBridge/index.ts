@@ -21,6 +21,8 @@ export * from './fs'
 export * from './path'
 export * from './log'
 export * from './notification'
+export * from './settings'
+export * from './deepLink'
 
 // 导入所有 API 实现
 import { autostartAPI } from './autostart'
@@ -38,6 +40,8 @@ import { fsAPI } from './fs'
 import { pathAPI } from './path'
 import { logAPI } from './log'
 import { notificationAPI } from './notification'
+import { settingsAPI } from './settings'
+import { deepLinkAPI } from './deepLink'
 
 /**
  * 统一的桌面应用 API 接口
@@ -59,6 +63,8 @@ export interface DesktopAPI {
     path: typeof pathAPI
     log: typeof logAPI
     notification: typeof notificationAPI
+    settings: typeof settingsAPI
+    deepLink: typeof deepLinkAPI
 }
 
 /**
@@ -103,7 +109,9 @@ export const desktopAPI: DesktopAPI = {
     fs: fsAPI,
     path: pathAPI,
     log: logAPI,
-    notification: notificationAPI
+    notification: notificationAPI,
+    settings: settingsAPI,
+    deepLink: deepLinkAPI
 }
 
 // 为了向后兼容，也单独导出各个 API
@@ -122,5 +130,7 @@ export {
     fsAPI,
     pathAPI,
     logAPI,
-    notificationAPI
+    notificationAPI,
+    settingsAPI,
+    deepLinkAPI
 }
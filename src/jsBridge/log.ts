@@ -3,6 +3,9 @@
  * 提供跨平台的日志记录和管理功能
  */
 
+import { upload } from '@tauri-apps/plugin-upload'
+import { coreAPI, errorMessage } from './core'
+import { eventAPI, type UnlistenFn } from './event'
 import { fsAPI } from './fs'
 import { pathAPI } from './path'
 
@@ -89,6 +92,17 @@ class GlobalLogConfig {
     }
 }
 
+/** 已经发出、还没有写入完成的日志，退出前等待它们写完 */
+const pendingWrites = new Set<Promise<unknown>>()
+
+/**
+ * 等待已经发出的日志全部写入
+ * 日志调用方通常不等待写入完成，退出前调用，避免最后几条日志丢失
+ */
+export const flushLogs = async (): Promise<void> => {
+    await Promise.allSettled([...pendingWrites])
+}
+
 export class LogManager {
     private logType: string
     private globalConfig: GlobalLogConfig
@@ -98,95 +112,6 @@ export class LogManager {
         this.globalConfig = GlobalLogConfig.getInstance()
     }
 
-    /**
-     * 格式化日期为 YYYY-MM-DD 格式
-     */
-    private formatDate(date: Date): string {
-        const year = date.getFullYear()
-        const month = String(date.getMonth() + 1).padStart(2, '0')
-        const day = String(date.getDate()).padStart(2, '0')
-        return `${year}-${month}-${day}`
-    }
-
-    /**
-     * 格式化时间戳
-     */
-    private formatTimestamp(date: Date): string {
-        const year = date.getFullYear()
-        const month = String(date.getMonth() + 1).padStart(2, '0')
-        const day = String(date.getDate()).padStart(2, '0')
-        const hours = String(date.getHours()).padStart(2, '0')
-        const minutes = String(date.getMinutes()).padStart(2, '0')
-        const seconds = String(date.getSeconds()).padStart(2, '0')
-        const ms = String(date.getMilliseconds()).padStart(3, '0')
-
-        return `${year}-${month}-${day} ${hours}:${minutes}:${seconds}.${ms}`
-    }
-
-    /**
-     * 获取日志文件路径
-     */
-    private async getLogFilePath(date: Date): Promise<string> {
-        const config = await this.globalConfig.getConfig()
-        const dateStr = this.formatDate(date)
-        const filename = `${dateStr}.${this.logType}.log`
-        return await pathAPI.join(config.logDir, filename)
-    }
-
-    /**
-     * 获取可用的日志文件路径（考虑文件大小限制）
-     */
-    private async getAvailableLogFilePath(date: Date): Promise<string> {
-        const config = await this.globalConfig.getConfig()
-        let baseFilePath = await this.getLogFilePath(date)
-
-        // 检查基础文件是否存在且是否超过大小限制
-        try {
-            if (await fsAPI.exists(baseFilePath)) {
-                const content = await fsAPI.readTextFile(baseFilePath)
-                const sizeInBytes = new TextEncoder().encode(content).length
-
-                if (sizeInBytes < config.maxFileSize) {
-                    return baseFilePath
-                }
-            } else {
-                return baseFilePath
-            }
-        } catch (error) {
-            // 文件不存在或读取失败，返回基础路径
-            return baseFilePath
-        }
-
-        // 如果基础文件超过大小限制，寻找下一个可用的编号文件
-        let counter = 1
-        while (true) {
-            const numberedFilePath = `${baseFilePath}.${counter}`
-
-            try {
-                if (await fsAPI.exists(numberedFilePath)) {
-                    const content = await fsAPI.readTextFile(numberedFilePath)
-                    const sizeInBytes = new TextEncoder().encode(content).length
-
-                    if (sizeInBytes < config.maxFileSize) {
-                        return numberedFilePath
-                    }
-                } else {
-                    return numberedFilePath
-                }
-            } catch (error) {
-                // 文件不存在，返回这个路径
-                return numberedFilePath
-            }
-
-            counter++
-
-            // 防止无限循环，最多尝试1000个文件
-            if (counter > 1000) {
-                throw new Error('无法找到可用的日志文件路径')
-            }
-        }
-    }
-
     /**
      * 序列化对象为字符串
      */
@@ -210,41 +135,25 @@ export class LogManager {
         return String(value)
     }
 
-    /**
-     * 格式化日志条目 - 模仿官方插件格式
-     * 格式: [TIMESTAMP][TARGET][LEVEL] MESSAGE
-     */
-    private formatLogEntry(level: LogLevel, target: string, message: any): string {
-        const timestamp = this.formatTimestamp(new Date())
-        const serializedMessage = this.serializeValue(message)
-
-        return `[${timestamp}][${target}][${level}] ${serializedMessage}`
-    }
-
     /**
      * 写入带级别的日志
+     * 日志通过 log_write 命令交给 Rust 端，与 Rust 日志写入同一个按天滚动的日志文件
      */
     async writeLog(level: LogLevel, message: any, target?: string): Promise<void> {
+        const write = coreAPI.invoke('log_write', {
+            level,
+            logType: this.logType,
+            module: target || this.logType.toUpperCase(),
+            message: this.serializeValue(message)
+        })
+        pendingWrites.add(write)
         try {
-            const now = new Date()
-            const logTarget = target || this.logType.toUpperCase()
-            const logEntry = this.formatLogEntry(level, logTarget, message) + '\n'
-
-            const filePath = await this.getAvailableLogFilePath(now)
-
-            try {
-                // 尝试追加到现有文件
-                const existingContent = (await fsAPI.exists(filePath)) ? await fsAPI.readTextFile(filePath) : ''
-                await fsAPI.writeTextFile(filePath, existingContent + logEntry)
-            } catch (error) {
-                // 如果追加失败，尝试创建新文件
-                await fsAPI.writeTextFile(filePath, logEntry)
-            }
-
-            console.log(`${level} 日志已写入 [${this.logType}]: ${filePath}`)
+            await write
         } catch (error) {
             console.error('写入日志失败:', error)
             throw error
+        } finally {
+            pendingWrites.delete(write)
         }
     }
 
@@ -283,35 +192,10 @@ export class LogManager {
 
     /**
      * 获取今日日志文件列表
+     * 日志文件由 Rust 端按 UTC 日期命名，文件列表同样由 Rust 端给出
      */
     async getTodayLogFiles(): Promise<string[]> {
-        const today = new Date()
-        const dateStr = this.formatDate(today)
-        const baseFilename = `${dateStr}.${this.logType}.log`
-
-        const config = await this.globalConfig.getConfig()
-        const files: string[] = []
-        const basePath = await pathAPI.join(config.logDir, baseFilename)
-
-        // 检查基础文件
-        if (await fsAPI.exists(basePath)) {
-            files.push(basePath)
-        }
-
-        // 检查编号文件
-        let counter = 1
-        while (counter <= 100) {
-            // 最多检查100个文件
-            const numberedPath = `${basePath}.${counter}`
-            if (await fsAPI.exists(numberedPath)) {
-                files.push(numberedPath)
-                counter++
-            } else {
-                break
-            }
-        }
-
-        return files
+        return coreAPI.invoke<string[]>('list_log_files')
     }
 
     /**
@@ -432,32 +316,14 @@ export const uploadAllLogsByDate = async (
 
         console.log(`开始上传 ${dateStr} 的所有日志文件...`)
 
-        // 读取日志目录中的所有文件
-        const dirEntries = await fsAPI.readDir(logDir)
-        const logFiles: string[] = []
-
-        for (const entry of dirEntries) {
-            if (!entry.isFile) continue
-
-            const fileName = entry.name
-
-            // 检查是否是指定日期的日志文件
-            const logFilePattern = new RegExp(`^${dateStr}\\..*\\.log(\\.\\d+)?$`)
-
-            if (logFilePattern.test(fileName)) {
-                const fullPath = await pathAPI.join(logDir, fileName)
-                logFiles.push(fullPath)
-            }
-        }
+        // 日志文件按 UTC 日期命名，由 Rust 端列出指定日期的文件（已按文件名排序）
+        const logFiles = await coreAPI.invoke<string[]>('list_log_files', { date: dateStr })
 
         if (logFiles.length === 0) {
             console.log(`未找到 ${dateStr} 的日志文件`)
             return { success: true, uploadedFiles: [], failedFiles: [], totalSize: 0 }
         }
 
-        // 按文件名排序
-        logFiles.sort()
-
         // 创建临时副本目录
         const tempDir = await pathAPI.join(logDir, `temp_upload_${Date.now()}`)
         await fsAPI.mkdir(tempDir, { recursive: true })
@@ -609,6 +475,86 @@ const formatBytes = (bytes: number): string => {
     return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i]
 }
 
+// Rust 端日志查看与控制
+export interface RustLogLine {
+    level: string | null
+    line: string
+}
+
+export interface RustLogPage {
+    file: string
+    lines: RustLogLine[]
+    total: number
+}
+
+export const rustLog = {
+    /** 获取当前的日志过滤规则，如 info,demo_lib::media_cache=debug */
+    getFilter: () => coreAPI.invoke<string>('get_log_filter'),
+
+    /** 替换日志过滤规则，返回生效后的规则 */
+    setFilter: (directives: string) => coreAPI.invoke<string>('set_log_filter', { directives }),
+
+    /** 设置模块的日志级别，不传 module 时设置默认级别 */
+    setLevel: (level: string, module?: string) => coreAPI.invoke<string>('set_log_level', { module, level }),
+
+    /** 从日志文件末尾分页读取，offset 为跳过的最新行数 */
+    readLines: (options: { date?: string; offset?: number; limit?: number; minLevel?: LogLevel } = {}) =>
+        coreAPI.invoke<RustLogPage>('read_log_lines', {
+            date: options.date,
+            offset: options.offset ?? 0,
+            limit: options.limit ?? 200,
+            minLevel: options.minLevel
+        }),
+
+    /** 开始实时接收新日志，返回取消函数 */
+    follow: async (handler: (line: RustLogLine) => void): Promise<UnlistenFn> => {
+        const unlisten = await eventAPI.listen<RustLogLine>('log-line', event => handler(event.payload))
+        await coreAPI.invoke('set_log_streaming', { enabled: true })
+        return () => {
+            unlisten()
+            coreAPI.invoke('set_log_streaming', { enabled: false })
+        }
+    }
+}
+
+// 服务端要求上传的日志（EventDeviceLogUpload）
+export interface DeviceLogUploadTask {
+    /** 任务 id，上传结束后用它结束任务 */
+    id: string
+    log_date: string
+    log_url: string
+    /** 压缩后的 zip 文件路径 */
+    archive: string
+    files: string[]
+    size: number
+}
+
+export interface DeviceLogUploadResult {
+    device_id: string
+    log_date: string
+    success: boolean
+    files: string[]
+    size: number
+    error: string | null
+}
+
+/**
+ * 响应服务端的日志上传请求
+ * Rust 端收集并压缩指定日期（UTC）的日志，这里通过 upload 插件上传压缩包，
+ * 上传结束后由 Rust 端删除压缩包并把结果回报服务端
+ * @param data base64 编码的 EventDeviceLogUpload
+ */
+export const handleDeviceLogUpload = async (data: string): Promise<DeviceLogUploadResult> => {
+    const task = await coreAPI.invoke<DeviceLogUploadTask>('prepare_device_log_upload', { data })
+    let error: string | null = null
+    try {
+        await upload(task.log_url, task.archive, undefined, new Map([['Content-Type', 'application/zip']]))
+    } catch (e) {
+        error = errorMessage(e)
+    }
+    return coreAPI.invoke<DeviceLogUploadResult>('finish_device_log_upload', { id: task.id, error })
+}
+
 /**
  * 统一的日志系统 API
  */
@@ -620,5 +566,8 @@ export const logAPI = {
     getLogDirectory,
     cleanupAllOldLogs,
     uploadAllLogsByDate,
-    formatBytes
+    handleDeviceLogUpload,
+    flushLogs,
+    formatBytes,
+    rustLog
 }
@@ -1,17 +1,44 @@
 /**
  * 系统打开器相关 API 抽象层
- * 提供打开 URL 和文件等功能
+ * 链接统一通过 openLink 等方法交给 Rust 按链接类型处理，前端不直接调用系统打开器
  */
 
-import { openUrl } from '@tauri-apps/plugin-opener'
+import { coreAPI } from './core'
+
+/**
+ * 链接类型
+ * - internal: 应用内部的深度链接（demo://），在应用内处理
+ * - trusted: 白名单域名下的 https 链接，直接用浏览器打开
+ * - external: 其他 http/https 链接，打开前需要用户确认
+ * - blocked: 不允许打开的链接
+ */
+export type LinkKind = 'internal' | 'trusted' | 'external' | 'blocked'
 
 export interface OpenerAPI {
-    /** 打开 URL */
-    openUrl(url: string): Promise<void>
+    /** 获取链接类型 */
+    classifyLink(url: string): Promise<LinkKind>
+    /** 按类型打开链接，用户取消时返回 false，不允许打开时抛出 code 为 BLOCKED_LINK 的 AppError */
+    openLink(url: string): Promise<boolean>
+    /** 打开应用链接消息中的链接，data 为 base64 编码的 MessageAppLink */
+    openAppLink(data: string): Promise<boolean>
+    /** 打开任务消息的按钮链接，data 为 base64 编码的 MessageTask */
+    openTaskLink(data: string): Promise<boolean>
 }
 
 export const openerAPI: OpenerAPI = {
-    openUrl: async (url: string) => {
-        return await openUrl(url)
+    classifyLink: async (url: string) => {
+        return await coreAPI.invoke<LinkKind>('classify_link', { url })
+    },
+
+    openLink: async (url: string) => {
+        return await coreAPI.invoke<boolean>('open_link', { url })
+    },
+
+    openAppLink: async (data: string) => {
+        return await coreAPI.invoke<boolean>('open_app_link', { data })
+    },
+
+    openTaskLink: async (data: string) => {
+        return await coreAPI.invoke<boolean>('open_task_link', { data })
     }
 }
@@ -0,0 +1,69 @@
+/**
+ * 应用设置 API 抽象层
+ * 设置由 Rust 侧的 settings 模块保存和校验，修改后所有窗口都会收到 settings-changed 事件
+ */
+
+import { listen, UnlistenFn } from '@tauri-apps/api/event'
+import { coreAPI } from './core'
+
+/** 主窗口关闭按钮的行为：隐藏到托盘、最小化、退出 */
+export type CloseBehavior = 'hide' | 'minimize' | 'quit'
+
+export interface Settings {
+    version: number
+    notifications: {
+        enabled: boolean
+        show_preview: boolean
+    }
+    do_not_disturb: {
+        enabled: boolean
+        /** 本地时间 HH:MM */
+        start: string
+        /** 本地时间 HH:MM，早于开始时间表示跨过午夜 */
+        end: string
+    }
+    close_behavior: CloseBehavior
+    tray_hint_shown: boolean
+    /** 全局快捷键，如 CmdOrCtrl+Alt+W，为空字符串时不注册 */
+    shortcuts: {
+        toggle_main_window: string
+        clear_unread: string
+        screenshot: string
+    }
+    autostart: boolean
+    language: 'zh-CN' | 'en-US'
+}
+
+/** 设置的部分修改，字段为 null 时恢复默认值 */
+export type SettingsPatch = {
+    [K in keyof Settings]?: Settings[K] extends object ? Partial<Settings[K]> | null : Settings[K] | null
+}
+
+export interface SettingsChanged {
+    settings: Settings
+    /** 发生变化的顶层字段 */
+    changed: (keyof Settings)[]
+}
+
+export interface SettingsAPI {
+    /** 获取设置 */
+    get(): Promise<Settings>
+    /** 修改设置，设置无效时抛出 code 为 INVALID_SETTINGS 的 AppError */
+    patch(patch: SettingsPatch): Promise<Settings>
+    /** 监听设置变化 */
+    onChanged(handler: (event: SettingsChanged) => void): Promise<UnlistenFn>
+}
+
+export const settingsAPI: SettingsAPI = {
+    get: async () => {
+        return await coreAPI.invoke<Settings>('get_settings')
+    },
+
+    patch: async (patch: SettingsPatch) => {
+        return await coreAPI.invoke<Settings>('patch_settings', { patch })
+    },
+
+    onChanged: async (handler: (event: SettingsChanged) => void) => {
+        return await listen<SettingsChanged>('settings-changed', event => handler(event.payload))
+    }
+}
@@ -3,6 +3,7 @@
  * 提供窗口控制功能
  */
 
+import { coreAPI } from './core'
 import { eventAPI } from './event'
 import { Window, getCurrentWindow, getAllWindows } from '@tauri-apps/api/window'
 
@@ -63,6 +64,31 @@ export interface WindowAPI {
     openFeaturesWindow(): Promise<void>
     /** 打开关于窗口 */
     openAboutWindow(): Promise<void>
+    /** 打开聊天弹出窗口，同一会话只会打开一个窗口，下次启动时自动恢复 */
+    openChatWindow(targetId: number, isRoom: boolean, title: string): Promise<void>
+    /** 获取打开的聊天弹出窗口 */
+    listChatWindows(): Promise<ChatWindow[]>
+    /** 清零聊天弹出窗口的未读数 */
+    markChatWindowRead(targetId: number, isRoom: boolean): Promise<void>
+    /** 获取通过 Rust 窗口管理打开的窗口 */
+    listOpenWindows(): Promise<OpenWindow[]>
+    /** 重置窗口布局，打开的窗口恢复默认大小并居中 */
+    resetWindowLayout(): Promise<void>
+}
+
+// 聊天弹出窗口
+export interface ChatWindow {
+    target_id: number
+    is_room: boolean
+    title: string
+}
+
+// 已打开窗口的信息
+export interface OpenWindow {
+    label: string
+    title: string
+    visible: boolean
+    focused: boolean
 }
 
 export const windowAPI: WindowAPI = {
@@ -199,11 +225,32 @@ export const windowAPI: WindowAPI = {
         }
     },
 
+    // 固定窗口由 Rust 窗口管理统一打开，已打开时直接显示并聚焦
     openFeaturesWindow: async () => {
-        await windowAPI.openOrFocusWindow(WINDOWS.FEATURES, 'Tauri功能演示', '/features')
+        await coreAPI.invoke('open_app_window', { label: WINDOWS.FEATURES })
     },
 
     openAboutWindow: async () => {
-        await windowAPI.openOrFocusWindow(WINDOWS.ABOUT, 'Tauri关于', '/about')
+        await coreAPI.invoke('open_app_window', { label: WINDOWS.ABOUT })
+    },
+
+    openChatWindow: async (targetId: number, isRoom: boolean, title: string) => {
+        await coreAPI.invoke('open_chat_window', { targetId, isRoom, title })
+    },
+
+    listChatWindows: async () => {
+        return await coreAPI.invoke<ChatWindow[]>('list_chat_windows')
+    },
+
+    markChatWindowRead: async (targetId: number, isRoom: boolean) => {
+        await coreAPI.invoke('mark_chat_window_read', { targetId, isRoom })
+    },
+
+    listOpenWindows: async () => {
+        return await coreAPI.invoke<OpenWindow[]>('list_open_windows')
+    },
+
+    resetWindowLayout: async () => {
+        await coreAPI.invoke('reset_window_layout')
     }
 }
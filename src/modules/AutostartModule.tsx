@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react'
-import { autostartAPI } from '../jsBridge'
+import { settingsAPI } from '../jsBridge'
 
 interface AutostartModuleProps {
     // 无需接收外部状态和回调，组件自己管理所有状态
@@ -10,33 +10,30 @@ export const AutostartModule: React.FC<AutostartModuleProps> = () => {
     const [isLoading, setIsLoading] = useState<boolean>(false)
 
     useEffect(() => {
-        // 获取自动启动状态（使用纯JavaScript API）
-        autostartAPI
-            .isEnabled()
-            .then((enabled: boolean) => {
-                setIsAutoStartEnabled(enabled)
-                console.log('自动启动状态:', enabled ? '已启用' : '已禁用')
+        // 开机自启通过应用设置管理，Rust 启动时已与系统中的实际状态同步
+        settingsAPI
+            .get()
+            .then(settings => {
+                setIsAutoStartEnabled(settings.autostart)
+                console.log('自动启动状态:', settings.autostart ? '已启用' : '已禁用')
             })
             .catch((err: any) => {
                 console.error('检查自动启动状态失败:', err)
             })
+        const unlisten = settingsAPI.onChanged(({ settings }) => setIsAutoStartEnabled(settings.autostart))
+        return () => {
+            unlisten.then(fn => fn())
+        }
     }, [])
 
     async function toggleAutoStart() {
         try {
             setIsLoading(true)
 
-            if (isAutoStartEnabled) {
-                // 如果已启用，则禁用（使用纯JavaScript API）
-                await autostartAPI.disable()
-                setIsAutoStartEnabled(false)
-                console.log('已禁用开机自启动')
-            } else {
-                // 如果已禁用，则启用（使用纯JavaScript API）
-                await autostartAPI.enable()
-                setIsAutoStartEnabled(true)
-                console.log('已启用开机自启动')
-            }
+            // 修改设置后由 Rust 调用自启插件，不直接操作插件，避免与设置不一致
+            const settings = await settingsAPI.patch({ autostart: !isAutoStartEnabled })
+            setIsAutoStartEnabled(settings.autostart)
+            console.log(settings.autostart ? '已启用开机自启动' : '已禁用开机自启动')
         } catch (error) {
             console.error('切换自动启动状态失败:', error)
             alert('切换自动启动状态失败: ' + error)
@@ -16,8 +16,10 @@ export const GreetingModule: React.FC<GreetingModuleProps> = () => {
 
     async function openTauriDocs() {
         try {
-            await openerAPI.openUrl('https://v2.tauri.app/zh-cn/learn/')
-            console.log('已打开Tauri文档')
+            const opened = await openerAPI.openLink('https://v2.tauri.app/zh-cn/learn/')
+            if (opened) {
+                console.log('已打开Tauri文档')
+            }
         } catch (error) {
             console.error('打开URL失败:', error)
             alert('打开URL失败: ' + error)
@@ -109,8 +111,8 @@ export const GreetingModule: React.FC<GreetingModuleProps> = () => {
             </button>
 
             <div style={{ fontSize: '10px', color: '#6b7280', lineHeight: '1.3' }}>
-                💡 使用<code>invoke</code>调用Rust后端函数，实现前后端跨语言通信。使用<code>openUrl</code>
-                可以在系统默认浏览器中打开链接。
+                💡 使用<code>invoke</code>调用Rust后端函数，实现前后端跨语言通信。使用<code>openLink</code>
+                按链接类型在系统默认浏览器中打开链接。
             </div>
         </div>
     )
@@ -16,7 +16,30 @@ interface SystemInfo {
 
 interface DeviceIdResponse {
     device_id: string
-    mac_address: string
+    // 只有用户开启 MAC 地址上报后才会返回
+    mac_address?: string
+    source: 'legacy' | 'machine_id' | 'random' | null
+    // 从旧版本迁移时的旧设备 id，服务端完成映射之前才会返回
+    previous_device_id?: string
+    fingerprint: string
+    fingerprint_signals: string[]
+}
+
+interface DeviceInventory {
+    device_id: string
+    app_version: string
+    os_type: string
+    os_version: string
+    platform: string
+    arch: string
+    locale: string | null
+    cpu_count: number
+    physical_core_count: number | null
+    total_memory: number
+    available_memory: number
+    display_count: number
+    uptime: number
+    network_interfaces: { name: string; mac_address?: string; addresses: string[] }[]
 }
 
 interface SystemInfoModuleProps {}
@@ -35,16 +58,23 @@ export const SystemInfoModule: React.FC<SystemInfoModuleProps> = () => {
             // 获取设备ID和MAC地址
             const deviceInfo = await coreAPI.invoke<DeviceIdResponse>('get_device_info')
 
-            // 获取应用版本
-            const appVer = await appAPI.getVersion()
+            // 获取应用版本和设备清单
+            const inventory = await coreAPI.invoke<DeviceInventory>('get_device_inventory')
+            const appVer = inventory.app_version
 
             // 构建签名数据
             const signData = {
                 os_name: platformInfo,
                 os_version: versionInfo || 'unknown',
                 imei: deviceInfo.device_id,
+                previous_imei: deviceInfo.previous_device_id,
+                fingerprint: deviceInfo.fingerprint,
                 mac_address: deviceInfo.mac_address,
-                app_version: appVer
+                app_version: appVer,
+                cpu_count: inventory.cpu_count,
+                total_memory: inventory.total_memory,
+                display_count: inventory.display_count,
+                locale: inventory.locale
             }
 
             // 为了模拟服务器注册，我们本地生成一个sign
@@ -61,6 +91,11 @@ export const SystemInfoModule: React.FC<SystemInfoModuleProps> = () => {
             console.log('设备注册信息:', signData)
             console.log('生成的sign:', sign)
 
+            // 注册成功后服务端已经记录了旧 id 到新 id 的映射，旧 id 不再需要保留
+            if (deviceInfo.previous_device_id) {
+                await coreAPI.invoke('confirm_device_id_migration')
+            }
+
             return {
                 deviceId: deviceInfo.device_id,
                 macAddress: deviceInfo.mac_address,
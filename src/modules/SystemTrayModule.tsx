@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react'
-import { coreAPI, eventAPI } from '../jsBridge'
+import { coreAPI, eventAPI, errorMessage } from '../jsBridge'
 
 interface SystemTrayModuleProps {
     // 无需接收外部状态和回调，组件自己管理所有状态
@@ -30,7 +30,7 @@ export const SystemTrayModule: React.FC<SystemTrayModuleProps> = () => {
             setUnreadCount(newCount)
         } catch (error) {
             console.error('增加未读数失败:', error)
-            alert('增加未读数失败: ' + error)
+            alert('增加未读数失败: ' + errorMessage(error))
         }
     }
 
@@ -40,7 +40,7 @@ export const SystemTrayModule: React.FC<SystemTrayModuleProps> = () => {
             setUnreadCount(newCount)
         } catch (error) {
             console.error('清除未读数失败:', error)
-            alert('清除未读数失败: ' + error)
+            alert('清除未读数失败: ' + errorMessage(error))
         }
     }
 
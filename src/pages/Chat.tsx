@@ -0,0 +1,52 @@
+import { useEffect, useState } from 'react'
+import { useSearchParams } from 'react-router-dom'
+import { invoke } from '@tauri-apps/api/core'
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow'
+
+interface ChatUnreadChanged {
+    target_id: number
+    is_room: boolean
+    count: number
+}
+
+// 聊天弹出窗口页面，由 Rust 侧的 chat_windows 模块按会话打开
+// 事件由 Rust 侧 emit_to 发给本窗口，只在当前窗口上监听，全局 listen 会收到发给所有窗口的事件
+const ChatPage = () => {
+    const [searchParams] = useSearchParams()
+    const targetId = Number(searchParams.get('id'))
+    const isRoom = searchParams.get('is_room') === 'true'
+    const [messages, setMessages] = useState<string[]>([])
+    const [unread, setUnread] = useState(0)
+
+    useEffect(() => {
+        invoke<number>('get_chat_window_unread', { targetId, isRoom }).then(setUnread)
+        const currentWindow = getCurrentWebviewWindow()
+
+        // payload 是 base64 编码的 MessagePush，只包含本会话的消息
+        const unlistenMessages = currentWindow.listen<string>('chat-messages', event => {
+            setMessages(prev => [...prev, event.payload])
+        })
+        const unlistenUnread = currentWindow.listen<ChatUnreadChanged>('chat-unread-changed', event => {
+            if (event.payload.target_id === targetId && event.payload.is_room === isRoom) {
+                setUnread(event.payload.count)
+            }
+        })
+        return () => {
+            unlistenMessages.then(fn => fn())
+            unlistenUnread.then(fn => fn())
+        }
+    }, [targetId, isRoom])
+
+    return (
+        <div style={{ padding: '20px' }}>
+            <h2>
+                {isRoom ? '群聊' : '单聊'}
+                {unread > 0 && <span style={{ marginLeft: '8px', color: '#e53e3e' }}>({unread} 条未读)</span>}
+            </h2>
+            <p style={{ marginTop: '10px', color: '#666' }}>会话 ID: {targetId}</p>
+            <p style={{ marginTop: '10px', color: '#666' }}>收到 {messages.length} 批消息</p>
+        </div>
+    )
+}
+
+export default ChatPage
@@ -0,0 +1,71 @@
+import { useEffect, useState } from 'react'
+import { useSearchParams } from 'react-router-dom'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+
+interface Notice {
+    id: number
+    title: string
+    content: string
+    send_at: number
+    staff_name: string
+    entity_name: string
+}
+
+// 系统公告弹窗页面，由 Rust 侧的 notices 模块打开
+const NoticePage = () => {
+    const [searchParams] = useSearchParams()
+    const [query, setQuery] = useState(searchParams.toString())
+    const [notice, setNotice] = useState<Notice | null>(null)
+
+    const params = new URLSearchParams(query)
+    const noticeId = Number(params.get('id'))
+    const announceType = params.get('announce_type')
+
+    // 窗口复用时，Rust 侧通过 notice-show 事件切换要展示的公告
+    useEffect(() => {
+        const unlisten = listen<string>('notice-show', event => setQuery(event.payload))
+        return () => {
+            unlisten.then(fn => fn())
+        }
+    }, [])
+
+    useEffect(() => {
+        if (!noticeId) {
+            setNotice(null)
+            return
+        }
+        invoke<Notice[]>('list_notices').then(list => {
+            setNotice(list.find(item => item.id === noticeId) || null)
+        })
+    }, [noticeId])
+
+    const acknowledge = async () => {
+        if (noticeId) {
+            await invoke('acknowledge_notice', { id: noticeId })
+        }
+    }
+
+    return (
+        <div style={{ padding: '20px' }}>
+            {notice ? (
+                <>
+                    <h2>{notice.title}</h2>
+                    <p style={{ color: '#888', fontSize: '12px' }}>
+                        {notice.entity_name} {notice.staff_name} {new Date(notice.send_at).toLocaleString()}
+                    </p>
+                    <div style={{ marginTop: '10px', whiteSpace: 'pre-wrap' }}>{notice.content}</div>
+                </>
+            ) : (
+                <h2>{announceType ? '新的宣发公告' : '公告已失效'}</h2>
+            )}
+            {noticeId > 0 && (
+                <button style={{ marginTop: '20px' }} onClick={acknowledge}>
+                    我知道了
+                </button>
+            )}
+        </div>
+    )
+}
+
+export default NoticePage